@@ -0,0 +1,530 @@
+use serde_json;
+use futures::future::{self, BoxFuture, FutureExt, TryFutureExt};
+use futures_timer::Delay;
+use std;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use error::{self, Error};
+use object::Object;
+use query::Query;
+use rancher;
+use resource::{self, Resource};
+use schema::{Schema, Schemas};
+use state::Transitioning;
+use auth::{self, Auth, Session};
+use trace::{self, ResponseEvent};
+use std::time::Instant;
+use client_config::{self, ClientConfig, RetryEvent};
+use transport::{AsyncTransport, IsahcTransport, Method, Request, Response};
+
+/// Asynchronous client for the Rancher API, available with the `async` feature.
+///
+/// It mirrors the resource API of `Rancher`, sharing the models and the error type, but every
+/// method returns a future. The futures own everything they need, so they can be spawned on any
+/// executor; like `Rancher`, the client is a cheap handle which can be cloned and shared.
+///
+/// Only a subset is available so far: `schemas`, `list`, `get`, `find_by_name`, `update`,
+/// `delete`, `invoke`, `wait_until` and `watch`, besides the raw requests. Lazy collections, the
+/// `ResourceClient` of types without a model and the `*_and_wait` helpers of containers are only
+/// provided by `Rancher`; the same results are reached with `request`, `invoke` and `wait_until`.
+///
+/// Timeouts, TLS, proxy and retry settings are given with `with_config` or
+/// `RancherBuilder::build_async`, see `IsahcTransport::with_config` for their limits.
+///
+/// ```no_run
+/// # extern crate futures;
+/// # extern crate rancher;
+/// # use rancher::{AsyncRancher, Container, Query};
+/// # fn main() {
+/// let rancher = AsyncRancher::new(String::from("http://rancher.test"), String::from("access"), String::from("secret"));
+/// let containers = futures::executor::block_on(rancher.list::<Container>("1a5", &Query::new())).unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AsyncRancher {
+    inner: Arc<Inner>
+}
+
+/// State shared by every clone of an `AsyncRancher`.
+struct Inner {
+    /// URL of the Rancher API.
+    url: String,
+    /// Credentials, and session token once logged in.
+    session: Session,
+    /// Transport used to send requests.
+    transport: Box<dyn AsyncTransport>,
+    /// Retry policy and hooks.
+    config: ClientConfig,
+    /// Schemas fetched from the server, on first use.
+    schemas: RwLock<Option<Arc<Schemas>>>
+}
+
+impl AsyncRancher {
+    /// Return an AsyncRancher struct filled with informations provided as arguments.
+    pub fn new(url: String, access_key: String, secret_key: String) -> AsyncRancher {
+        return AsyncRancher::with_transport(url, access_key, secret_key, IsahcTransport::new());
+    }
+    /// Return an AsyncRancher struct applying the timeouts, TLS, proxy and retry settings of
+    /// `config`, see `IsahcTransport::with_config` for the TLS settings isahc doesn't support.
+    pub fn with_config(url: String, access_key: String, secret_key: String, config: ClientConfig) -> Result<AsyncRancher, Error> {
+        let transport = IsahcTransport::with_config(&config)?;
+        return Ok(AsyncRancher::with_transport_and_config(url, access_key, secret_key, transport, config));
+    }
+    /// Return an AsyncRancher struct sending its requests through `transport` instead of isahc.
+    pub fn with_transport<T>(url: String, access_key: String, secret_key: String, transport: T) -> AsyncRancher
+        where T: AsyncTransport + 'static {
+        return AsyncRancher::with_transport_and_config(url, access_key, secret_key, transport, ClientConfig::new());
+    }
+    /// Return an AsyncRancher struct sending its requests through `transport` and retrying them
+    /// according to `config`. Timeouts are up to the transport.
+    pub fn with_transport_and_config<T>(url: String, access_key: String, secret_key: String, transport: T, config: ClientConfig) -> AsyncRancher
+        where T: AsyncTransport + 'static {
+        let auth = Auth::Basic { access_key, secret_key };
+        return AsyncRancher::with_parts(url, auth, Box::new(transport), config);
+    }
+    /// Return an AsyncRancher struct authenticating with `auth` and sending its requests through
+    /// `transport`.
+    pub fn with_auth<T>(url: String, auth: Auth, transport: T) -> AsyncRancher
+        where T: AsyncTransport + 'static {
+        return AsyncRancher::with_parts(url, auth, Box::new(transport), ClientConfig::new());
+    }
+    /// Return an AsyncRancher struct from every setting, see `RancherBuilder::build_async`.
+    pub(crate) fn with_parts(url: String, auth: Auth, transport: Box<dyn AsyncTransport>, config: ClientConfig) -> AsyncRancher {
+        return AsyncRancher {
+            inner: Arc::new(Inner {
+                url,
+                session: Session::new(auth),
+                transport,
+                config,
+                schemas: RwLock::new(None)
+            })
+        };
+    }
+
+    /// Return the schemas of every resource type, fetched from `/schemas` on the first call and
+    /// cached afterwards.
+    pub fn schemas(&self) -> BoxFuture<'static, Result<Arc<Schemas>, Error>> {
+        if let Some(ref schemas) = *rancher::read(&self.inner.schemas) {
+            return future::ok(schemas.clone()).boxed();
+        }
+        return self.refresh_schemas();
+    }
+
+    /// Fetch `/schemas` again, see `Rancher::refresh_schemas`.
+    pub fn refresh_schemas(&self) -> BoxFuture<'static, Result<Arc<Schemas>, Error>> {
+        let client = self.clone();
+        let url = format!("{}/schemas", self.inner.url);
+        return self.pages(url, Vec::new())
+            .and_then(move |items| {
+                let list = match items.iter().map(Schema::from_value).collect::<Result<Vec<Schema>, Error>>() {
+                    Ok(v) => v,
+                    Err(e) => return future::err(e),
+                };
+                let schemas = Arc::new(Schemas::new(list));
+                *rancher::write(&client.inner.schemas) = Some(schemas.clone());
+                return future::ok(schemas);
+            })
+            .boxed();
+    }
+
+    /// Send a GET request to the Rancher API.
+    pub fn call_api(&self, path: &str) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        return self.request(Method::Get, path, None);
+    }
+    /// Send a POST request to the Rancher API with data.
+    pub fn post_api(&self, path: &str, data: &str) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        return self.request(Method::Post, path, Some(data));
+    }
+    /// Send a PUT request to the Rancher API with data.
+    pub fn put_api(&self, path: &str, data: &str) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        return self.request(Method::Put, path, Some(data));
+    }
+    /// Send a DELETE request to the Rancher API.
+    pub fn delete_api(&self, path: &str) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        return self.request(Method::Delete, path, None);
+    }
+
+    /// Send a request to the Rancher API and decode the JSON response, see `Rancher::request`.
+    pub fn request(&self, method: Method, path: &str, body: Option<&str>) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        let url = format!("{}{}", self.inner.url, path);
+        return self.request_url(method, &url, body);
+    }
+
+    /// Send a request to an absolute URL, e.g. a link returned by the Rancher API, and decode the
+    /// JSON response.
+    ///
+    /// With `Auth::Login`, the client logs in before the first request, and again once if the
+    /// session token is rejected.
+    pub fn request_url(&self, method: Method, url: &str, body: Option<&str>) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        let client = self.clone();
+        let url = url.to_string();
+        let body = body.map(|v| v.to_string());
+        let login = if self.inner.session.needs_login() {
+            self.login()
+        } else {
+            future::ok(()).boxed()
+        };
+        return login
+            .and_then(move |_| client.send(method, &url, body.as_deref())
+                .then(move |result| match result {
+                    Err(ref e) if client.inner.session.is_login() && auth::is_unauthorized(e) => {
+                        let retry = client.clone();
+                        client.login().and_then(move |_| retry.send(method, &url, body.as_deref())).boxed()
+                    },
+                    result => future::ready(result).boxed(),
+                }))
+            .boxed();
+    }
+
+    /// Log in with the username and password of `Auth::Login` and keep the new session token,
+    /// see `Rancher::login`.
+    pub fn login(&self) -> BoxFuture<'static, Result<(), Error>> {
+        let request = match self.inner.session.login_request(&self.inner.url) {
+            Some(v) => v,
+            None => return future::ok(()).boxed(),
+        };
+        let client = self.clone();
+        return self.inner.transport.send(&request)
+            .and_then(move |response| future::ready(rancher::decode_response(response)
+                .and_then(|result| client.inner.session.set_token(&result))))
+            .boxed();
+    }
+
+    /// Send a request, retrying it according to the retry policy, and decode the response.
+    fn send(&self, method: Method, url: &str, body: Option<&str>) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        let request = rancher::build_request(&self.inner.session, method, url, body);
+        return self.attempt(request, 0);
+    }
+
+    /// Send a request which was already retried `attempt` times, see `Rancher::send`.
+    fn attempt(&self, request: Request, attempt: u32) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        let client = self.clone();
+        return self.transmit(&request)
+            .then(move |result| {
+                let method = request.method;
+                let policy = match client.inner.config.retry {
+                    Some(ref v) if v.allows(method, attempt) => v,
+                    _ => return future::ready(result.and_then(rancher::decode_response)).boxed(),
+                };
+                let (status, delay) = match result {
+                    Ok(ref response) if client_config::is_retryable_status(response.status) => {
+                        (Some(response.status), policy.delay(attempt, client_config::retry_after(response)))
+                    },
+                    Ok(response) => return future::ready(rancher::decode_response(response)).boxed(),
+                    Err(ref e) if e.is_connection_error() => (None, policy.delay(attempt, None)),
+                    Err(e) => return future::err(e).boxed(),
+                };
+                if let Some(ref hook) = client.inner.config.on_retry {
+                    hook(&RetryEvent { method, url: &request.url, attempt: attempt + 1, delay, status, error: result.as_ref().err() });
+                }
+                return Delay::new(delay).then(move |_| client.attempt(request, attempt + 1)).boxed();
+            })
+            .boxed();
+    }
+
+    /// Send a request through the transport, logging it and calling the request and response
+    /// hooks, see `Rancher::transmit`.
+    fn transmit(&self, request: &Request) -> BoxFuture<'static, Result<Response, Error>> {
+        let config = &self.inner.config;
+        if !trace::enabled() && config.on_request.is_none() && config.on_response.is_none() {
+            return self.inner.transport.send(request);
+        }
+        let redacted = trace::redact_request(request);
+        trace::log_request(&redacted);
+        if let Some(ref hook) = config.on_request {
+            hook(&redacted);
+        }
+        let on_response = config.on_response.clone();
+        let start = Instant::now();
+        let method = request.method;
+        let url = request.url.clone();
+        return self.inner.transport.send(request)
+            .map(move |result| {
+                let body = match result {
+                    Ok(ref response) if trace::bodies_enabled() || on_response.is_some() => Some(trace::response_body(response)),
+                    _ => None,
+                };
+                let event = ResponseEvent {
+                    method,
+                    url: &url,
+                    status: result.as_ref().ok().map(|v| v.status),
+                    duration: start.elapsed(),
+                    body: body.as_deref(),
+                    error: result.as_ref().err(),
+                };
+                trace::log_response(&event);
+                if let Some(ref hook) = on_response {
+                    hook(&event);
+                }
+                return result;
+            })
+            .boxed();
+    }
+
+    /// Invoke an action on a resource through the URL advertised in its `actions` map, see
+    /// `Rancher::invoke`.
+    pub fn invoke<T: Object>(&self, resource: &T, action: &str, input: Option<&serde_json::Value>) -> BoxFuture<'static, Result<serde_json::Value, Error>> {
+        let url = match rancher::action_url(resource, action) {
+            Ok(v) => v,
+            Err(e) => return future::err(e).boxed()
+        };
+        let body = input.map(|v| v.to_string());
+        return self.request_url(Method::Post, &url, body.as_deref());
+    }
+
+    /// Get all resources of a collection matching `query`, following every page.
+    pub fn list<R: Resource + Send + 'static>(&self, parent: &R::Parent, query: &Query) -> BoxFuture<'static, Result<Vec<R>, Error>> {
+        let url = format!("{}{}", self.inner.url, query.append_to(&R::collection_path(parent)));
+        return self.pages(url, Vec::new())
+            .and_then(|items| future::ready(items.iter().map(R::parse).collect()))
+            .boxed();
+    }
+
+    /// Get a resource by ID.
+    ///
+    /// `Error::NotFound` is returned if Rancher answers with a 404.
+    pub fn get<R: Resource + Send + 'static>(&self, parent: &R::Parent, id: &R::Id) -> BoxFuture<'static, Result<R, Error>> {
+        let path = R::resource_path(parent, id);
+        let id = id.to_string();
+        return self.call_api(&path)
+            .map(move |result| match result {
+                Ok(result) => R::parse(&result),
+                Err(e) => Err(resource::not_found::<R>(e, &id)),
+            })
+            .boxed();
+    }
+
+    /// Get the first resource of a collection whose name, or hostname for hosts, is `name`.
+    pub fn find_by_name<R: Resource + Send + 'static>(&self, parent: &R::Parent, name: &str) -> BoxFuture<'static, Result<R, Error>> {
+        let query = Query::new().eq(R::NAME_FIELD, name).limit(1);
+        let path = query.append_to(&R::collection_path(parent));
+        let name = name.to_string();
+        return self.call_api(&path)
+            .and_then(move |results| future::ready(match error::collection_data(&results) {
+                Ok(data) => match data.first() {
+                    Some(item) => R::parse(item),
+                    None => Err(resource::name_not_found::<R>(&name)),
+                },
+                Err(e) => Err(e),
+            }))
+            .boxed();
+    }
+
+    /// Update a resource with the fields provided in `data`.
+    pub fn update<R: Resource + Send + 'static>(&self, parent: &R::Parent, id: &R::Id, data: &serde_json::Value) -> BoxFuture<'static, Result<R, Error>> {
+        let path = R::resource_path(parent, id);
+        return self.put_api(&path, &data.to_string())
+            .and_then(|result| future::ready(R::parse(&result)))
+            .boxed();
+    }
+
+    /// Remove a resource.
+    pub fn delete<R: Resource>(&self, parent: &R::Parent, id: &R::Id) -> BoxFuture<'static, Result<(), Error>> {
+        let path = R::resource_path(parent, id);
+        return self.delete_api(&path).map_ok(|_| ()).boxed();
+    }
+
+    /// Get a resource by ID and poll it until `predicate` returns true, see `wait_until`.
+    pub fn watch<R, F>(&self, parent: &R::Parent, id: &R::Id, predicate: F, timeout: Duration, poll_interval: Duration) -> BoxFuture<'static, Result<R, Error>>
+        where R: Resource + Send + 'static, F: Fn(&R) -> bool + Send + Sync + 'static {
+        let client = self.clone();
+        return self.get::<R>(parent, id)
+            .and_then(move |resource| {
+                if predicate(&resource) {
+                    return future::ok(resource).boxed();
+                }
+                return client.wait_until(&resource, predicate, timeout, poll_interval);
+            })
+            .boxed();
+    }
+
+    /// Poll a resource through its `self` link until `predicate` returns true and return its last
+    /// version, see `Rancher::wait_until`. The delay between two requests doesn't block any thread.
+    pub fn wait_until<T, F>(&self, resource: &T, predicate: F, timeout: Duration, poll_interval: Duration) -> BoxFuture<'static, Result<T, Error>>
+        where T: Object + DeserializeOwned + Send + 'static, F: Fn(&T) -> bool + Send + Sync + 'static {
+        let url = match resource.self_link() {
+            Some(v) => v.to_string(),
+            None => return future::err(Error::Decode(format!("resource {} without `self` link", resource.id()))).boxed()
+        };
+        return self.poll(url, Arc::new(predicate), Instant::now() + timeout, timeout, poll_interval);
+    }
+
+    /// Fetch the resource at `url` until `predicate` returns true or `deadline` is passed.
+    fn poll<T, F>(&self, url: String, predicate: Arc<F>, deadline: Instant, timeout: Duration, poll_interval: Duration) -> BoxFuture<'static, Result<T, Error>>
+        where T: Object + DeserializeOwned + Send + 'static, F: Fn(&T) -> bool + Send + Sync + 'static {
+        let client = self.clone();
+        return self.request_url(Method::Get, &url, None)
+            .and_then(move |result| {
+                let current: T = match serde_json::from_value(result) {
+                    Ok(v) => v,
+                    Err(e) => return future::err(Error::Json(e)).boxed(),
+                };
+                if current.transitioning() == Some(&Transitioning::Error) {
+                    return future::err(Error::Transition(format!("{}: {}", current.id(),
+                                                                 current.transitioning_message().unwrap_or("unknown error")))).boxed();
+                }
+                if predicate(&current) {
+                    return future::ok(current).boxed();
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    return future::err(Error::Timeout(format!("{} did not reach the expected state in {:?}", current.id(), timeout))).boxed();
+                }
+                return Delay::new(std::cmp::min(poll_interval, deadline - now))
+                    .then(move |_| client.poll(url, predicate, deadline, timeout, poll_interval))
+                    .boxed();
+            })
+            .boxed();
+    }
+
+    /// Fetch the page at `url` and the following ones, appending their items to `items`.
+    fn pages(&self, url: String, mut items: Vec<serde_json::Value>) -> BoxFuture<'static, Result<Vec<serde_json::Value>, Error>> {
+        let rancher = self.clone();
+        return self.request_url(Method::Get, &url, None)
+            .and_then(move |results| {
+                match error::collection_data(&results) {
+                    Ok(data) => items.extend(data.iter().cloned()),
+                    Err(e) => return future::err(e).boxed(),
+                }
+                return match results["pagination"]["next"].as_str() {
+                    Some(next) => rancher.pages(next.to_string(), items),
+                    None => future::ok(items).boxed(),
+                };
+            })
+            .boxed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use container::Container;
+    use state::ContainerState;
+    use client_config::{TlsConfig, RetryPolicy};
+    use transport::MockTransport;
+    use test_util::{async_rancher as rancher, async_rancher_with_config};
+
+    #[test]
+    fn list() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/projects/1a5/containers?state=running", 200, r#"{
+                "data": [{"id": "1i1"}],
+                "pagination": {"next": "http://rancher.test/projects/1a5/containers?state=running&marker=m2"}
+            }"#)
+            .on(Method::Get, "/projects/1a5/containers?state=running&marker=m2", 200, r#"{
+                "data": [{"id": "1i2", "name": "web"}]
+            }"#));
+        let rancher = rancher(transport.clone());
+        let containers = block_on(rancher.list::<Container>("1a5", &Query::new().eq("state", "running"))).unwrap();
+
+        let ids: Vec<&str> = containers.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["1i1", "1i2"]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn get() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/projects/1a5/containers/1i1", 200, r#"{"id": "1i1", "name": "web"}"#)
+            .on(Method::Get, "/projects/1a5/containers?name=web&limit=1", 200, r#"{"data": [{"id": "1i1", "name": "web"}]}"#)
+            .on(Method::Get, "/projects/1a5/containers?name=db&limit=1", 200, r#"{"data": []}"#));
+        let rancher = rancher(transport);
+
+        let container = block_on(rancher.get::<Container>("1a5", "1i1")).unwrap();
+        assert_eq!(container.name, Some(String::from("web")));
+        let container = block_on(rancher.find_by_name::<Container>("1a5", "web")).unwrap();
+        assert_eq!(container.id, "1i1");
+        match block_on(rancher.get::<Container>("1a5", "1i2")) {
+            Err(Error::NotFound { .. }) => {},
+            other => panic!("Expected NotFound, got {:?}", other)
+        }
+        match block_on(rancher.find_by_name::<Container>("1a5", "db")) {
+            Err(Error::NotFound { .. }) => {},
+            other => panic!("Expected NotFound, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn retry() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/projects", 503, "Service Unavailable")
+            .on(Method::Get, "/projects", 200, r#"{"data": []}"#));
+        let config = ClientConfig::new().retry(RetryPolicy::new().initial_backoff(Duration::from_millis(1)));
+        let rancher = async_rancher_with_config(transport.clone(), config);
+        block_on(rancher.call_api("/projects")).unwrap();
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn with_config() {
+        let config = ClientConfig::new().tls(TlsConfig::new().ca_file("/etc/ssl/internal-ca.pem"));
+        assert!(AsyncRancher::with_config(String::from("https://rancher.test"), String::from("access"),
+                                          String::from("secret"), config).is_ok());
+        let config = ClientConfig::new().tls(TlsConfig::new().pin_public_key("sha256//YhKJKSzoTt2b5FP18fvpHo7fJYqQCjAa3HWY3tvRMwE="));
+        match AsyncRancher::with_config(String::from("https://rancher.test"), String::from("access"), String::from("secret"), config) {
+            Err(Error::Config(_)) => {},
+            Err(e) => panic!("Expected Config, got {:?}", e),
+            Ok(_) => panic!("Expected Config, got a client"),
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/projects", 200, r#"{"data": [{"id": "1a5", "secretValue": "s3cr3t"}]}"#));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let (on_request, on_response) = (requests.clone(), responses.clone());
+        let config = ClientConfig::new()
+            .on_request(move |request| on_request.lock().unwrap().push(request.headers.clone()))
+            .on_response(move |response| on_response.lock().unwrap().push((response.status, response.body.map(String::from))));
+        let rancher = async_rancher_with_config(transport, config);
+        block_on(rancher.call_api("/projects")).unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].iter().all(|(_, value)| !value.contains("YWNjZXNzOnNlY3JldA==")));
+        let responses = responses.lock().unwrap();
+        assert_eq!(responses[0].0, Some(200));
+        assert!(!responses[0].1.as_ref().unwrap().contains("s3cr3t"));
+    }
+
+    #[test]
+    fn watch() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/projects/1a5/containers/1i1", 200, r#"{
+                "id": "1i1", "state": "starting", "links": {"self": "http://rancher.test/projects/1a5/containers/1i1"}
+            }"#)
+            .on(Method::Get, "/projects/1a5/containers/1i1", 200, r#"{
+                "id": "1i1", "state": "starting", "links": {"self": "http://rancher.test/projects/1a5/containers/1i1"}
+            }"#)
+            .on(Method::Get, "/projects/1a5/containers/1i1", 200, r#"{
+                "id": "1i1", "state": "running", "links": {"self": "http://rancher.test/projects/1a5/containers/1i1"}
+            }"#));
+        let rancher = rancher(transport.clone());
+        let container = block_on(rancher.watch::<Container, _>("1a5", "1i1", |c| c.state == Some(ContainerState::Running),
+                                                              Duration::from_secs(5), Duration::from_millis(1))).unwrap();
+        assert_eq!(container.state, Some(ContainerState::Running));
+        assert_eq!(transport.requests().len(), 3);
+
+        match block_on(rancher.watch::<Container, _>("1a5", "1i1", |c| c.state == Some(ContainerState::Stopped),
+                                                     Duration::from_millis(20), Duration::from_millis(5))) {
+            Err(Error::Timeout(_)) => {},
+            other => panic!("Expected Timeout, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn schemas() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/schemas", 200, r#"{"data": [{"id": "volume", "pluralName": "volumes"}]}"#));
+        let rancher = rancher(transport.clone());
+        assert_eq!(block_on(rancher.schemas()).unwrap().types(), vec!["volume"]);
+        block_on(rancher.schemas()).unwrap();
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
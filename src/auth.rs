@@ -0,0 +1,174 @@
+use std::fmt;
+use std::sync::RwLock;
+use base64;
+use serde_json;
+use error::Error;
+use transport::{Method, Request};
+
+/// How requests are authenticated.
+///
+/// ```no_run
+/// # use rancher::{Rancher, Auth};
+/// # fn main() -> Result<(), rancher::Error> {
+/// let rancher = Rancher::builder()
+///     .url("https://rancher.example.com/v2-beta")
+///     .auth(Auth::login("admin", "password"))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq)]
+pub enum Auth {
+    /// API key, sent with HTTP basic authentication.
+    Basic {
+        /// Access key of the API key.
+        access_key: String,
+        /// Secret key of the API key.
+        secret_key: String
+    },
+    /// API token, sent as `Authorization: Bearer`.
+    Bearer(String),
+    /// Local authentication: log in with a username and a password through `POST /token` and
+    /// send the session token it returns as a cookie. A new one is requested if it expires.
+    Login {
+        /// Username of the account.
+        username: String,
+        /// Password of the account.
+        password: String
+    },
+    /// No authentication, for servers without access control.
+    None,
+}
+
+impl Auth {
+    /// Return an `Auth::Basic`.
+    pub fn basic(access_key: &str, secret_key: &str) -> Auth {
+        return Auth::Basic { access_key: access_key.to_string(), secret_key: secret_key.to_string() };
+    }
+
+    /// Return an `Auth::Bearer`.
+    pub fn bearer(token: &str) -> Auth {
+        return Auth::Bearer(token.to_string());
+    }
+
+    /// Return an `Auth::Login`.
+    pub fn login(username: &str, password: &str) -> Auth {
+        return Auth::Login { username: username.to_string(), password: password.to_string() };
+    }
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match *self {
+            Auth::Basic { ref access_key, .. } => write!(f, "Basic {{ access_key: {:?}, secret_key: <redacted> }}", access_key),
+            Auth::Bearer(_) => write!(f, "Bearer(<redacted>)"),
+            Auth::Login { ref username, .. } => write!(f, "Login {{ username: {:?}, password: <redacted> }}", username),
+            Auth::None => write!(f, "None"),
+        };
+    }
+}
+
+/// Authentication of a client, with the session token obtained by `Auth::Login`.
+pub(crate) struct Session {
+    auth: Auth,
+    token: RwLock<Option<String>>,
+}
+
+impl Session {
+    /// Return a session without token.
+    pub(crate) fn new(auth: Auth) -> Session {
+        return Session {
+            auth,
+            token: RwLock::new(None),
+        };
+    }
+
+    /// Return whether a session token is needed and missing.
+    pub(crate) fn needs_login(&self) -> bool {
+        return self.is_login() && self.token().is_none();
+    }
+
+    /// Return whether requests are authenticated by a session token.
+    pub(crate) fn is_login(&self) -> bool {
+        return matches!(self.auth, Auth::Login { .. });
+    }
+
+    /// Return the header authenticating requests, if any.
+    pub(crate) fn header(&self) -> Option<(String, String)> {
+        return match self.auth {
+            Auth::Basic { ref access_key, ref secret_key } => {
+                let credentials = format!("{}:{}", access_key, secret_key);
+                Some((String::from("Authorization"), format!("Basic {}", base64::encode(&credentials))))
+            },
+            Auth::Bearer(ref token) => Some((String::from("Authorization"), format!("Bearer {}", token))),
+            Auth::Login { .. } => self.token().map(|token| (String::from("Cookie"), format!("token={}", token))),
+            Auth::None => None,
+        };
+    }
+
+    /// Return the request logging in to the Rancher API at `url`, for `Auth::Login`.
+    pub(crate) fn login_request(&self, url: &str) -> Option<Request> {
+        return match self.auth {
+            Auth::Login { ref username, ref password } => Some(Request {
+                method: Method::Post,
+                url: format!("{}/token", url),
+                headers: vec![(String::from("Content-Type"), String::from("application/json"))],
+                body: Some(json!({
+                    "code": format!("{}:{}", username, password),
+                    "authProvider": "localauthconfig"
+                }).to_string()),
+            }),
+            _ => None,
+        };
+    }
+
+    /// Keep the session token returned by a login request.
+    pub(crate) fn set_token(&self, response: &serde_json::Value) -> Result<(), Error> {
+        let token = match response["jwt"].as_str() {
+            Some(v) => v.to_string(),
+            None => return Err(Error::Decode(String::from("token without `jwt`"))),
+        };
+        *match self.token.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        } = Some(token);
+        return Ok(());
+    }
+
+    /// Return the current session token.
+    fn token(&self) -> Option<String> {
+        return match self.token.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+    }
+}
+
+/// Return whether the server rejected the credentials of a request.
+pub(crate) fn is_unauthorized(error: &Error) -> bool {
+    return matches!(*error, Error::Unauthorized(_));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers() {
+        let header = |auth: Auth| Session::new(auth).header();
+        assert_eq!(header(Auth::basic("access", "secret")),
+                   Some((String::from("Authorization"), String::from("Basic YWNjZXNzOnNlY3JldA=="))));
+        assert_eq!(header(Auth::bearer("token-abc")),
+                   Some((String::from("Authorization"), String::from("Bearer token-abc"))));
+        assert_eq!(header(Auth::None), None);
+
+        let session = Session::new(Auth::login("admin", "password"));
+        assert!(session.needs_login());
+        assert_eq!(session.header(), None);
+        session.set_token(&json!({"type": "token", "jwt": "eyJhbGciOi"})).unwrap();
+        assert!(!session.needs_login());
+        assert_eq!(session.header(), Some((String::from("Cookie"), String::from("token=eyJhbGciOi"))));
+
+        assert!(!format!("{:?}", Auth::login("admin", "hunter2")).contains("hunter2"));
+    }
+}
@@ -0,0 +1,304 @@
+use std;
+use std::time::Duration;
+use serde_json;
+use super::Rancher;
+use error::Error;
+use config::Profile;
+use auth::Auth;
+use client_config::{ClientConfig, RetryPolicy, TlsConfig, ProxyConfig};
+use transport::{Transport, CurlTransport};
+#[cfg(feature = "async")]
+use async_rancher::AsyncRancher;
+#[cfg(feature = "async")]
+use transport::IsahcTransport;
+
+/// Versions of the Rancher API recognized at the end of its URL.
+const API_VERSIONS: [&str; 2] = ["/v2-beta", "/v1"];
+
+/// Builder of a `Rancher` client, returned by `Rancher::builder`.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use rancher::{Rancher, RetryPolicy};
+/// # fn main() -> Result<(), rancher::Error> {
+/// let rancher = Rancher::builder()
+///     .url("https://rancher.example.com/v2-beta")
+///     .credentials("access", "secret")
+///     .timeout(Duration::from_secs(30))
+///     .user_agent("deploy-bot/1.0")
+///     .retry(RetryPolicy::new())
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct RancherBuilder {
+    url: Option<String>,
+    access_key: Option<String>,
+    secret_key: Option<String>,
+    environment: Option<String>,
+    auth: Option<Auth>,
+    config: ClientConfig,
+    transport: Option<Box<dyn Transport>>,
+}
+
+impl RancherBuilder {
+    /// Return an empty builder.
+    pub fn new() -> RancherBuilder {
+        return RancherBuilder::default();
+    }
+
+    /// Set the URL of the Rancher API, see `normalize_url`.
+    pub fn url(mut self, url: &str) -> RancherBuilder {
+        self.url = Some(url.to_string());
+        return self;
+    }
+
+    /// Set the access and secret keys of the Rancher API.
+    pub fn credentials(mut self, access_key: &str, secret_key: &str) -> RancherBuilder {
+        self.access_key = Some(access_key.to_string());
+        self.secret_key = Some(secret_key.to_string());
+        return self;
+    }
+
+    /// Authenticate with `auth` instead of the access and secret keys.
+    pub fn auth(mut self, auth: Auth) -> RancherBuilder {
+        self.auth = Some(auth);
+        return self;
+    }
+
+    /// Set the default environment, by ID or name, used by `Rancher::default_environment`.
+    pub fn environment(mut self, environment: &str) -> RancherBuilder {
+        self.environment = Some(environment.to_string());
+        return self;
+    }
+
+    /// Replace every client setting at once.
+    pub fn config(mut self, config: ClientConfig) -> RancherBuilder {
+        self.config = config;
+        return self;
+    }
+
+    /// Set the maximum duration to establish a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> RancherBuilder {
+        self.config = self.config.connect_timeout(timeout);
+        return self;
+    }
+
+    /// Set the maximum duration of a whole request.
+    pub fn timeout(mut self, timeout: Duration) -> RancherBuilder {
+        self.config = self.config.timeout(timeout);
+        return self;
+    }
+
+    /// Set the `User-Agent` header sent with every request.
+    pub fn user_agent(mut self, user_agent: &str) -> RancherBuilder {
+        self.config = self.config.user_agent(user_agent);
+        return self;
+    }
+
+    /// Set the TLS settings.
+    pub fn tls(mut self, tls: TlsConfig) -> RancherBuilder {
+        self.config = self.config.tls(tls);
+        return self;
+    }
+
+    /// Set the proxy settings.
+    pub fn proxy(mut self, proxy: ProxyConfig) -> RancherBuilder {
+        self.config = self.config.proxy(proxy);
+        return self;
+    }
+
+    /// Retry idempotent requests according to `policy`.
+    pub fn retry(mut self, policy: RetryPolicy) -> RancherBuilder {
+        self.config = self.config.retry(policy);
+        return self;
+    }
+
+    /// Send requests through `transport` instead of curl. Timeouts, user agent, TLS and proxy
+    /// settings are then up to the transport.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> RancherBuilder {
+        self.transport = Some(Box::new(transport));
+        return self;
+    }
+
+    /// Check the settings and return the client.
+    ///
+    /// `Error::InvalidUrl` is returned if the URL is invalid, `Error::Config` if the URL or the
+    /// credentials are missing. Use `Auth::None` for servers without access control.
+    pub fn build(mut self) -> Result<Rancher, Error> {
+        let (url, auth) = self.url_and_auth()?;
+        let transport = match self.transport {
+            Some(v) => v,
+            None => Box::new(CurlTransport::with_config(&self.config)),
+        };
+        return Ok(Rancher::with_parts(url, auth, transport, self.config, self.environment));
+    }
+
+    /// Check the settings and return an asynchronous client, see `build` and
+    /// `IsahcTransport::with_config`.
+    ///
+    /// The default environment is ignored since `AsyncRancher` has none. A transport given to
+    /// `transport` can't be used asynchronously, so `Error::Config` is returned in that case.
+    #[cfg(feature = "async")]
+    pub fn build_async(mut self) -> Result<AsyncRancher, Error> {
+        if self.transport.is_some() {
+            return Err(Error::Config(String::from("`transport` only applies to `build`")));
+        }
+        let (url, auth) = self.url_and_auth()?;
+        let transport = IsahcTransport::with_config(&self.config)?;
+        return Ok(AsyncRancher::with_parts(url, auth, Box::new(transport), self.config));
+    }
+
+    /// Return the normalized URL and the authentication.
+    fn url_and_auth(&mut self) -> Result<(String, Auth), Error> {
+        let url = match self.url {
+            Some(ref v) => normalize_url(v)?,
+            None => return Err(Error::Config(String::from("missing URL, set `url` or RANCHER_URL"))),
+        };
+        let auth = match (self.auth.take(), self.access_key.take(), self.secret_key.take()) {
+            (Some(auth), _, _) => auth,
+            (None, Some(access_key), Some(secret_key)) => Auth::Basic { access_key, secret_key },
+            (None, None, _) => return Err(Error::Config(String::from("missing access key, set `accessKey` or RANCHER_ACCESS_KEY"))),
+            (None, _, None) => return Err(Error::Config(String::from("missing secret key, set `secretKey` or RANCHER_SECRET_KEY"))),
+        };
+        return Ok((url, auth));
+    }
+
+    /// Override the settings with those of a profile: URL, credentials, possibly given as a
+    /// `tokenKey` of the form `access:secret`, default environment, and TLS and proxy settings.
+    pub(crate) fn profile(mut self, profile: &Profile) -> Result<RancherBuilder, Error> {
+        let set = |value: &Option<String>| value.as_ref().filter(|v| !v.is_empty()).cloned();
+        if let Some(token) = profile.extra.get("tokenKey").and_then(|v| v.as_str()) {
+            let mut parts = token.splitn(2, ':');
+            if let (Some(access_key), Some(secret_key)) = (parts.next(), parts.next()) {
+                self = self.credentials(access_key, secret_key);
+            }
+        }
+        self.url = set(&profile.url).or(self.url);
+        self.access_key = set(&profile.access_key).or(self.access_key);
+        self.secret_key = set(&profile.secret_key).or(self.secret_key);
+        self.environment = set(&profile.environment).or(self.environment);
+        let extra = serde_json::Value::Object(profile.extra.clone());
+        self.config = self.config.tls(TlsConfig::from_value(&extra)?);
+        if let Some(proxy) = ProxyConfig::from_value(&extra)? {
+            self.config = self.config.proxy(proxy);
+        }
+        return Ok(self);
+    }
+
+    /// Override the settings with the `RANCHER_*` environment variables.
+    pub(crate) fn env(self) -> RancherBuilder {
+        return self.vars(|name| std::env::var(name).ok());
+    }
+
+    /// Override the settings with the `RANCHER_*` variables returned by `var`.
+    pub(crate) fn vars<F>(mut self, var: F) -> RancherBuilder
+        where F: Fn(&str) -> Option<String> {
+        let var = |name: &str| var(name).filter(|v| !v.is_empty());
+        self.url = var("RANCHER_URL").or(self.url);
+        self.access_key = var("RANCHER_ACCESS_KEY").or(self.access_key);
+        self.secret_key = var("RANCHER_SECRET_KEY").or(self.secret_key);
+        self.environment = var("RANCHER_ENVIRONMENT").or(self.environment);
+        return self;
+    }
+}
+
+/// Return the base URL of the Rancher API from `url`.
+///
+/// The scheme must be `http` or `https`. A trailing `/` or `/schemas` is removed, and the URL
+/// must end with an API version, `/v2-beta` or `/v1`, unless it has no path at all, in which case
+/// `/v2-beta` is appended.
+pub fn normalize_url(url: &str) -> Result<String, Error> {
+    let rest = match url.find("://") {
+        Some(i) => match &url[..i] {
+            "http" | "https" => &url[i + 3..],
+            scheme => return Err(Error::InvalidUrl(format!("unsupported scheme `{}` in `{}`", scheme, url))),
+        },
+        None => return Err(Error::InvalidUrl(format!("missing scheme in `{}`, e.g. https://", url))),
+    };
+    let host_end = rest.find('/').unwrap_or(rest.len());
+    if host_end == 0 {
+        return Err(Error::InvalidUrl(format!("missing host in `{}`", url)));
+    }
+    if rest.contains('?') || rest.contains('#') {
+        return Err(Error::InvalidUrl(format!("unexpected query or fragment in `{}`", url)));
+    }
+    let mut base = url.trim_end_matches('/');
+    if base.ends_with("/schemas") {
+        base = &base[..base.len() - "/schemas".len()];
+    }
+    if API_VERSIONS.iter().any(|version| base.ends_with(version)) {
+        return Ok(base.to_string());
+    }
+    if base.len() == url.len() - rest.len() + host_end {
+        return Ok(format!("{}{}", base, API_VERSIONS[0]));
+    }
+    return Err(Error::InvalidUrl(format!("`{}` doesn't end with an API version ({})", url, API_VERSIONS.join(" or "))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use transport::{MockTransport, Method};
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_url("https://rancher.example.com/v2-beta").unwrap(), "https://rancher.example.com/v2-beta");
+        assert_eq!(normalize_url("https://rancher.example.com/v2-beta/schemas").unwrap(), "https://rancher.example.com/v2-beta");
+        assert_eq!(normalize_url("http://10.0.0.1:8080/v1/").unwrap(), "http://10.0.0.1:8080/v1");
+        assert_eq!(normalize_url("https://example.com/rancher/v2-beta").unwrap(), "https://example.com/rancher/v2-beta");
+        assert_eq!(normalize_url("https://rancher.example.com/").unwrap(), "https://rancher.example.com/v2-beta");
+
+        for url in &["rancher.example.com/v2-beta", "ftp://rancher.example.com/v1", "https:///v2-beta",
+                     "https://rancher.example.com/v3", "https://rancher.example.com/v2-beta?limit=1"] {
+            match normalize_url(url) {
+                Err(Error::InvalidUrl(_)) => {},
+                other => panic!("Expected InvalidUrl for {}, got {:?}", url, other)
+            }
+        }
+    }
+
+    #[test]
+    fn build() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/v2-beta/projects", 200, r#"{"data": []}"#));
+        let rancher = Rancher::builder()
+            .url("http://rancher.test/v2-beta/schemas")
+            .credentials("access", "secret")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        rancher.call_api("/projects").unwrap();
+        assert_eq!(transport.requests()[0].url, "http://rancher.test/v2-beta/projects");
+
+        match Rancher::builder().url("http://rancher.test/v2-beta").build() {
+            Err(Error::Config(_)) => {},
+            Err(e) => panic!("Expected Config, got {:?}", e),
+            Ok(_) => panic!("Expected Config, got a client"),
+        }
+    }
+
+    #[test]
+    fn sources() {
+        let profile: Profile = serde_json::from_value(json!({
+            "url": "http://file.test/v2-beta",
+            "tokenKey": "file-access:file-secret",
+            "environment": "Default"
+        })).unwrap();
+        let builder = RancherBuilder::new().profile(&profile).unwrap();
+        assert_eq!(builder.secret_key, Some(String::from("file-secret")));
+        let builder = builder.vars(|name| match name {
+            "RANCHER_URL" => Some(String::from("http://env.test")),
+            "RANCHER_ACCESS_KEY" => Some(String::from("env-access")),
+            "RANCHER_SECRET_KEY" => Some(String::from("env-secret")),
+            "RANCHER_ENVIRONMENT" => Some(String::new()),
+            _ => None,
+        });
+        assert_eq!(builder.url, Some(String::from("http://env.test")));
+        assert_eq!(builder.access_key, Some(String::from("env-access")));
+        assert_eq!(builder.secret_key, Some(String::from("env-secret")));
+        assert_eq!(builder.environment, Some(String::from("Default")));
+    }
+}
@@ -0,0 +1,280 @@
+use serde_json;
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+use super::Rancher;
+use error::Error;
+use schema::Schema;
+use collection::Collection;
+use resource;
+use query::Query;
+use transport::Method;
+
+/// Client for any resource type listed in `/schemas`, e.g. volumes or load balancers, which
+/// doesn't have a dedicated model in this crate.
+///
+/// Resources are returned as `serde_json::Value` unless a type is given with `typed`. Requests
+/// are checked against the schema of the type before being sent.
+///
+/// ```no_run
+/// # use rancher::{Rancher, Query};
+/// # let rancher = Rancher::new(String::from("http://rancher.test"), String::from("access"), String::from("secret"));
+/// let volumes = rancher.resource("volume").list("1a5", &Query::new().eq("driver", "local")).unwrap();
+/// for volume in volumes {
+///     println!("{}", volume["name"]);
+/// }
+/// ```
+pub struct ResourceClient<'a, T = serde_json::Value> {
+    rancher: &'a Rancher,
+    resource_type: String,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: DeserializeOwned> ResourceClient<'a, T> {
+    /// Return a client for resources of `resource_type`.
+    pub(crate) fn new(rancher: &'a Rancher, resource_type: &str) -> ResourceClient<'a, T> {
+        return ResourceClient {
+            rancher,
+            resource_type: resource_type.to_string(),
+            marker: PhantomData,
+        };
+    }
+
+    /// Return the same client, decoding resources as `U` instead.
+    pub fn typed<U: DeserializeOwned>(self) -> ResourceClient<'a, U> {
+        return ResourceClient::new(self.rancher, &self.resource_type);
+    }
+
+    /// Return the schema of the resource type.
+    pub fn schema(&self) -> Result<Schema, Error> {
+        return Ok(self.rancher.schemas()?.schema(&self.resource_type)?.clone());
+    }
+
+    /// Return a lazy handle on the resources of an environment.
+    pub fn collection(&self, environment_id: &str) -> Result<Collection<'_, T>, Error> {
+        let path = collection_path(&self.schema()?, environment_id)?;
+        return Ok(Collection::new(self.rancher, path, parse::<T>));
+    }
+
+    /// Get all resources from an environment matching `query`.
+    pub fn list(&self, environment_id: &str, query: &Query) -> Result<Vec<T>, Error> {
+        self.schema()?.validate_query(query)?;
+        return self.collection(environment_id)?.query(query).iter().collect();
+    }
+
+    /// Get a resource from an environment by ID.
+    ///
+    /// `Error::NotFound` is returned if Rancher answers with a 404, like `Rancher::get`.
+    pub fn get(&self, environment_id: &str, id: &str) -> Result<T, Error> {
+        let path = resource_path(&self.schema()?, environment_id, id)?;
+        return match self.rancher.call_api(&path) {
+            Ok(result) => parse(&result),
+            Err(e) => Err(resource::type_not_found(e, &self.resource_type, &id)),
+        };
+    }
+
+    /// Create a resource in an environment with the fields provided in `data`.
+    pub fn create(&self, environment_id: &str, data: &serde_json::Value) -> Result<T, Error> {
+        let schema = self.schema()?;
+        schema.validate_create(data)?;
+        let path = collection_path(&schema, environment_id)?;
+        let result = self.rancher.post_api(&path, &data.to_string())?;
+        return parse(&result);
+    }
+
+    /// Update a resource from an environment with the fields provided in `data`.
+    pub fn update(&self, environment_id: &str, id: &str, data: &serde_json::Value) -> Result<T, Error> {
+        let schema = self.schema()?;
+        schema.validate_update(data)?;
+        let path = resource_path(&schema, environment_id, id)?;
+        let result = self.rancher.put_api(&path, &data.to_string())?;
+        return parse(&result);
+    }
+
+    /// Remove a resource from an environment.
+    pub fn delete(&self, environment_id: &str, id: &str) -> Result<(), Error> {
+        let path = resource_path(&self.schema()?, environment_id, id)?;
+        self.rancher.delete_api(&path)?;
+        return Ok(());
+    }
+
+    /// Invoke an action on a resource from an environment through the URL advertised in its
+    /// `actions` map, and return the output of the action.
+    ///
+    /// # Arguments
+    ///
+    /// * `environment_id` - The environment ID.
+    /// * `id` - The resource ID.
+    /// * `action` - The action name, which must be listed in the schema of the type.
+    /// * `input` - An optional JSON input for actions requiring one.
+    ///
+    pub fn action(&self, environment_id: &str, id: &str, action: &str, input: Option<&serde_json::Value>) -> Result<serde_json::Value, Error> {
+        let schema = self.schema()?;
+        schema.validate_action(action)?;
+        let path = resource_path(&schema, environment_id, id)?;
+        let resource = self.rancher.call_api(&path)?;
+        let url = match resource["actions"][action].as_str() {
+            Some(v) => v.to_string(),
+            None => {
+                let mut available: Vec<&str> = match resource["actions"].as_object() {
+                    Some(actions) => actions.keys().map(|v| v.as_str()).collect(),
+                    None => Vec::new(),
+                };
+                available.sort();
+                return Err(Error::UnavailableAction(format!("`{}` on {}, available actions: [{}]",
+                                                            action, id, available.join(", "))));
+            }
+        };
+        let body = input.map(|v| v.to_string());
+        return self.rancher.request_url(Method::Post, &url, body.as_deref());
+    }
+}
+
+/// Return the path of the collection of a type in an environment, named by the `pluralName` of
+/// its schema or else by its `collection` link.
+fn collection_path(schema: &Schema, environment_id: &str) -> Result<String, Error> {
+    let plural = match schema.plural_name {
+        Some(ref v) => v.as_str(),
+        None => match schema.links.get("collection").and_then(|url| url.trim_end_matches('/').rsplit('/').next()) {
+            Some(v) if !v.is_empty() => v,
+            _ => return Err(Error::Validation(format!("the schema of `{}` names no collection", schema.id))),
+        },
+    };
+    return Ok(format!("/projects/{}/{}", environment_id, plural));
+}
+
+/// Return the path of a resource in an environment.
+fn resource_path(schema: &Schema, environment_id: &str, id: &str) -> Result<String, Error> {
+    return Ok(format!("{}/{}", collection_path(schema, environment_id)?, id));
+}
+
+/// Decode an object returned by the Rancher API.
+fn parse<T: DeserializeOwned>(value: &serde_json::Value) -> Result<T, Error> {
+    return Ok(serde_json::from_value(value.clone())?);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use transport::MockTransport;
+    use test_util::rancher;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Volume {
+        id: String,
+        name: String,
+    }
+
+    fn transport() -> Arc<MockTransport> {
+        let schemas = r#"{
+            "data": [
+                {
+                    "id": "volume",
+                    "pluralName": "volumes",
+                    "resourceFields": {
+                        "name": {"type": "string", "create": true, "required": true},
+                        "driver": {"type": "string", "create": true},
+                        "description": {"type": "string", "create": true, "update": true}
+                    },
+                    "collectionFilters": {
+                        "driver": {"modifiers": ["eq", "ne"]}
+                    },
+                    "resourceActions": {
+                        "activate": {"output": "volume"},
+                        "deactivate": {"output": "volume"}
+                    }
+                },
+                {
+                    "id": "scalePolicy",
+                    "links": {"collection": "http://rancher.test/scalePolicies"}
+                },
+                {
+                    "id": "snapshotBackup"
+                }
+            ]
+        }"#;
+        let volume = r#"{
+            "id": "1v1",
+            "name": "data",
+            "actions": {"deactivate": "http://rancher.test/projects/1a5/volumes/1v1?action=deactivate"}
+        }"#;
+        return Arc::new(MockTransport::new()
+            .on(Method::Get, "/schemas", 200, schemas)
+            .on(Method::Get, "/projects/1a5/volumes?driver=local", 200, &format!(r#"{{"data": [{}]}}"#, volume))
+            .on(Method::Get, "/projects/1a5/volumes/1v1", 200, volume)
+            .on(Method::Get, "/projects/1a5/scalePolicies", 200, r#"{"data": [{"id": "1sp1"}]}"#)
+            .on(Method::Post, "/projects/1a5/volumes", 201, volume)
+            .on(Method::Post, "/projects/1a5/volumes/1v1?action=deactivate", 202, volume));
+    }
+
+    #[test]
+    fn list() {
+        let rancher = rancher(transport());
+        let volumes = rancher.resource("volume").list("1a5", &Query::new().eq("driver", "local")).unwrap();
+        assert_eq!(volumes[0]["name"], "data");
+
+        let volumes = rancher.resource("volume").typed::<Volume>().list("1a5", &Query::new().eq("driver", "local")).unwrap();
+        assert_eq!(volumes, vec![Volume { id: String::from("1v1"), name: String::from("data") }]);
+
+        match rancher.resource("volume").list("1a5", &Query::new().eq("size", "10")) {
+            Err(Error::Validation(_)) => {},
+            other => panic!("Expected Validation, got {:?}", other)
+        }
+        match rancher.resource("snapshot").list("1a5", &Query::new()) {
+            Err(Error::Validation(_)) => {},
+            other => panic!("Expected Validation, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn create() {
+        let transport = transport();
+        let rancher = rancher(transport.clone());
+        let volume: Volume = rancher.resource("volume").typed().create("1a5", &json!({"name": "data", "driver": "local"})).unwrap();
+        assert_eq!(volume.id, "1v1");
+
+        match rancher.resource("volume").create("1a5", &json!({"driver": "local"})) {
+            Err(Error::Validation(_)) => {},
+            other => panic!("Expected Validation, got {:?}", other)
+        }
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn action() {
+        let rancher = rancher(transport());
+        let result = rancher.resource("volume").action("1a5", "1v1", "deactivate", None).unwrap();
+        assert_eq!(result["id"], "1v1");
+
+        match rancher.resource("volume").action("1a5", "1v1", "activate", None) {
+            Err(Error::UnavailableAction(_)) => {},
+            other => panic!("Expected UnavailableAction, got {:?}", other)
+        }
+        match rancher.resource("volume").action("1a5", "1v1", "snapshot", None) {
+            Err(Error::Validation(_)) => {},
+            other => panic!("Expected Validation, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn get() {
+        let rancher = rancher(transport());
+        let volume: Volume = rancher.resource("volume").typed().get("1a5", "1v1").unwrap();
+        assert_eq!(volume.name, "data");
+        match rancher.resource("volume").get("1a5", "1v2") {
+            Err(e @ Error::NotFound { .. }) => assert_eq!(e.to_string(), "not found: no volume with ID `1v2`"),
+            other => panic!("Expected NotFound, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn collection_name() {
+        let rancher = rancher(transport());
+        let policies = rancher.resource("scalePolicy").list("1a5", &Query::new()).unwrap();
+        assert_eq!(policies[0]["id"], "1sp1");
+        match rancher.resource("snapshotBackup").list("1a5", &Query::new()) {
+            Err(Error::Validation(_)) => {},
+            other => panic!("Expected Validation, got {:?}", other)
+        }
+    }
+}
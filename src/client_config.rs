@@ -0,0 +1,534 @@
+use std;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use serde_json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use error::Error;
+use transport::{Method, Request, Response};
+use trace::ResponseEvent;
+
+/// Hook called before each retry.
+pub type RetryHook = Arc<dyn Fn(&RetryEvent) + Send + Sync>;
+
+/// Hook called before each request is sent.
+pub type RequestHook = Arc<dyn Fn(&Request) + Send + Sync>;
+
+/// Hook called once each request is done.
+pub type ResponseHook = Arc<dyn Fn(&ResponseEvent) + Send + Sync>;
+
+/// Settings of the HTTP client used by `Rancher`.
+///
+/// Everything is disabled by default: requests never time out and are never retried.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use rancher::Rancher;
+/// use rancher::client_config::{ClientConfig, RetryPolicy};
+///
+/// let config = ClientConfig::new()
+///     .connect_timeout(Duration::from_secs(5))
+///     .timeout(Duration::from_secs(30))
+///     .retry(RetryPolicy::new().max_retries(3))
+///     .on_retry(|retry| eprintln!("retrying {} {} in {:?}", retry.method.as_str(), retry.url, retry.delay));
+/// let rancher = Rancher::with_config(String::from("https://rancher.example.com/v2-beta"), String::from("access"),
+///                                    String::from("secret"), config);
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientConfig {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) on_retry: Option<RetryHook>,
+    pub(crate) on_request: Option<RequestHook>,
+    pub(crate) on_response: Option<ResponseHook>,
+    pub(crate) tls: TlsConfig,
+    pub(crate) proxy: Option<ProxyConfig>,
+}
+
+impl ClientConfig {
+    /// Return the default settings.
+    pub fn new() -> ClientConfig {
+        return ClientConfig::default();
+    }
+
+    /// Set the maximum duration to establish a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> ClientConfig {
+        self.connect_timeout = Some(timeout);
+        return self;
+    }
+
+    /// Set the maximum duration of a whole request, from connection to the end of the response.
+    pub fn timeout(mut self, timeout: Duration) -> ClientConfig {
+        self.timeout = Some(timeout);
+        return self;
+    }
+
+    /// Set the `User-Agent` header sent with every request.
+    pub fn user_agent(mut self, user_agent: &str) -> ClientConfig {
+        self.user_agent = Some(user_agent.to_string());
+        return self;
+    }
+
+    /// Retry idempotent requests according to `policy`.
+    pub fn retry(mut self, policy: RetryPolicy) -> ClientConfig {
+        self.retry = Some(policy);
+        return self;
+    }
+
+    /// Set how the identity of the server is checked and how the client identifies itself.
+    pub fn tls(mut self, tls: TlsConfig) -> ClientConfig {
+        self.tls = tls;
+        return self;
+    }
+
+    /// Send requests through a proxy, or directly with `ProxyConfig::direct`, instead of following
+    /// the proxy environment variables.
+    pub fn proxy(mut self, proxy: ProxyConfig) -> ClientConfig {
+        self.proxy = Some(proxy);
+        return self;
+    }
+
+    /// Call `hook` before each retry, e.g. to log it.
+    pub fn on_retry<F>(mut self, hook: F) -> ClientConfig
+        where F: Fn(&RetryEvent) + Send + Sync + 'static {
+        self.on_retry = Some(Arc::new(hook));
+        return self;
+    }
+
+    /// Call `hook` before each request is sent, retries included, e.g. to log it. Credentials
+    /// are redacted from the headers and the body.
+    pub fn on_request<F>(mut self, hook: F) -> ClientConfig
+        where F: Fn(&Request) + Send + Sync + 'static {
+        self.on_request = Some(Arc::new(hook));
+        return self;
+    }
+
+    /// Call `hook` once each request is done, with its status, duration and body. Credentials
+    /// are redacted from the body.
+    pub fn on_response<F>(mut self, hook: F) -> ClientConfig
+        where F: Fn(&ResponseEvent) + Send + Sync + 'static {
+        self.on_response = Some(Arc::new(hook));
+        return self;
+    }
+}
+
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_struct("ClientConfig")
+            .field("connect_timeout", &self.connect_timeout)
+            .field("timeout", &self.timeout)
+            .field("user_agent", &self.user_agent)
+            .field("retry", &self.retry)
+            .field("on_retry", &self.on_retry.is_some())
+            .field("on_request", &self.on_request.is_some())
+            .field("on_response", &self.on_response.is_some())
+            .field("tls", &self.tls)
+            .field("proxy", &self.proxy)
+            .finish();
+    }
+}
+
+/// PEM encoded certificates or key, read from a file or given directly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pem {
+    /// Path of a PEM file.
+    File(PathBuf),
+    /// Content of a PEM file.
+    Bytes(Vec<u8>),
+}
+
+/// TLS settings: trusted certificate authorities, client certificate and public key pinning.
+///
+/// By default the system certificate store is trusted and no client certificate is sent.
+///
+/// In the profiles of a configuration file (see `Config`), the same settings are read from
+/// the optional `cacert` (PEM content, as written by the official rancher CLI), `caFile`,
+/// `clientCert`, `clientKey`, `pinnedPublicKeys` and `insecure` fields.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TlsConfig {
+    pub(crate) ca: Option<Pem>,
+    pub(crate) client_cert: Option<(Pem, Pem)>,
+    pub(crate) pinned_public_keys: Vec<String>,
+    pub(crate) insecure: bool,
+}
+
+impl TlsConfig {
+    /// Return the default settings.
+    pub fn new() -> TlsConfig {
+        return TlsConfig::default();
+    }
+
+    /// Trust the certificate authorities of a PEM file instead of the system store.
+    pub fn ca_file<P: Into<PathBuf>>(mut self, path: P) -> TlsConfig {
+        self.ca = Some(Pem::File(path.into()));
+        return self;
+    }
+
+    /// Trust the PEM encoded certificate authorities of `pem` instead of the system store.
+    pub fn ca_pem(mut self, pem: &[u8]) -> TlsConfig {
+        self.ca = Some(Pem::Bytes(pem.to_vec()));
+        return self;
+    }
+
+    /// Authenticate with a client certificate and its private key (mutual TLS).
+    pub fn client_cert(mut self, cert: Pem, key: Pem) -> TlsConfig {
+        self.client_cert = Some((cert, key));
+        return self;
+    }
+
+    /// Only accept servers whose public key matches one of the pinned ones, given as
+    /// `sha256//` followed by the base64 encoded SHA-256 hash of the key.
+    pub fn pin_public_key(mut self, key: &str) -> TlsConfig {
+        self.pinned_public_keys.push(key.to_string());
+        return self;
+    }
+
+    /// Accept any certificate and hostname. This disables every protection of TLS against
+    /// man-in-the-middle attacks and is only meant for lab setups with self-signed certificates.
+    pub fn danger_accept_invalid_certs(mut self, insecure: bool) -> TlsConfig {
+        self.insecure = insecure;
+        return self;
+    }
+
+    /// Read the settings from a configuration file.
+    pub(crate) fn from_value(config: &serde_json::Value) -> Result<TlsConfig, Error> {
+        let mut tls = TlsConfig::new();
+        if let Some(pem) = config["cacert"].as_str().filter(|v| !v.is_empty()) {
+            tls = tls.ca_pem(pem.as_bytes());
+        }
+        if let Some(path) = config["caFile"].as_str() {
+            tls = tls.ca_file(path);
+        }
+        match (config["clientCert"].as_str(), config["clientKey"].as_str()) {
+            (Some(cert), Some(key)) => tls = tls.client_cert(Pem::File(PathBuf::from(cert)), Pem::File(PathBuf::from(key))),
+            (None, None) => {},
+            _ => return Err(Error::Config(String::from("`clientCert` and `clientKey` must be set together"))),
+        }
+        if let Some(keys) = config["pinnedPublicKeys"].as_array() {
+            for key in keys {
+                match key.as_str() {
+                    Some(v) => tls = tls.pin_public_key(v),
+                    None => return Err(Error::Config(String::from("`pinnedPublicKeys` must only contain strings"))),
+                }
+            }
+        }
+        if let Some(insecure) = config["insecure"].as_bool() {
+            tls = tls.danger_accept_invalid_certs(insecure);
+        }
+        return Ok(tls);
+    }
+}
+
+/// Proxy settings.
+///
+/// Unless a `ProxyConfig` is given to `ClientConfig::proxy`, the one of `ProxyConfig::from_env` is
+/// used for each request. HTTP, HTTPS and SOCKS proxies are supported, depending on the scheme of their URL, e.g.
+/// `socks5h://proxy.example.com:1080`.
+///
+/// In the profiles of a configuration file (see `Config`), the same settings are read from
+/// the optional `proxy` (an empty string to connect directly), `proxyUsername`, `proxyPassword`
+/// and `noProxy` fields.
+#[derive(Clone, Default, PartialEq)]
+pub struct ProxyConfig {
+    pub(crate) url: Option<String>,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+    pub(crate) no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Send requests through the proxy at `url`.
+    pub fn new(url: &str) -> ProxyConfig {
+        return ProxyConfig {
+            url: Some(url.to_string()),
+            ..ProxyConfig::default()
+        };
+    }
+
+    /// Connect directly to the server, whatever the environment says.
+    pub fn direct() -> ProxyConfig {
+        return ProxyConfig::default();
+    }
+
+    /// Read the settings for requests to `url` from the environment variables, in upper or lower
+    /// case: `HTTP_PROXY` for an `http://` URL, `HTTPS_PROXY` otherwise, falling back to
+    /// `ALL_PROXY`, and `NO_PROXY`.
+    pub fn from_env(url: &str) -> ProxyConfig {
+        return ProxyConfig::from_vars(url, |name| std::env::var(name).ok());
+    }
+
+    /// Authenticate to the proxy.
+    pub fn credentials(mut self, username: &str, password: &str) -> ProxyConfig {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+        return self;
+    }
+
+    /// Connect directly to `host`, to its subdomains if it's a domain name. `*` matches every host.
+    pub fn no_proxy(mut self, host: &str) -> ProxyConfig {
+        self.no_proxy.push(host.to_string());
+        return self;
+    }
+
+    /// Read the settings for requests to `url` from the variables returned by `var`.
+    pub(crate) fn from_vars<F>(url: &str, var: F) -> ProxyConfig
+        where F: Fn(&str) -> Option<String> {
+        let first = |names: &[&str]| names.iter()
+            .filter_map(|name| var(name))
+            .find(|v| !v.is_empty());
+        let scheme = if url.to_lowercase().starts_with("http://") {
+            ["http_proxy", "HTTP_PROXY"]
+        } else {
+            ["https_proxy", "HTTPS_PROXY"]
+        };
+        let mut proxy = match first(&scheme).or_else(|| first(&["all_proxy", "ALL_PROXY"])) {
+            Some(url) => ProxyConfig::new(&url),
+            None => ProxyConfig::direct(),
+        };
+        if let Some(hosts) = first(&["no_proxy", "NO_PROXY"]) {
+            for host in hosts.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                proxy = proxy.no_proxy(host);
+            }
+        }
+        return proxy;
+    }
+
+    /// Read the settings from a configuration file, or return `None` if it has none.
+    pub(crate) fn from_value(config: &serde_json::Value) -> Result<Option<ProxyConfig>, Error> {
+        let mut proxy = match config["proxy"].as_str() {
+            Some("") => ProxyConfig::direct(),
+            Some(url) => ProxyConfig::new(url),
+            None => return Ok(None),
+        };
+        match (config["proxyUsername"].as_str(), config["proxyPassword"].as_str()) {
+            (Some(username), Some(password)) => proxy = proxy.credentials(username, password),
+            (None, None) => {},
+            _ => return Err(Error::Config(String::from("`proxyUsername` and `proxyPassword` must be set together"))),
+        }
+        let hosts: Vec<&str> = match config["noProxy"] {
+            serde_json::Value::Null => Vec::new(),
+            serde_json::Value::String(ref hosts) => hosts.split(',').map(str::trim).collect(),
+            serde_json::Value::Array(ref hosts) => match hosts.iter().map(|v| v.as_str()).collect() {
+                Some(v) => v,
+                None => return Err(Error::Config(String::from("`noProxy` must only contain strings"))),
+            },
+            _ => return Err(Error::Config(String::from("`noProxy` must be a string or a list of strings"))),
+        };
+        for host in hosts.into_iter().filter(|v| !v.is_empty()) {
+            proxy = proxy.no_proxy(host);
+        }
+        return Ok(Some(proxy));
+    }
+}
+
+impl fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_struct("ProxyConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("no_proxy", &self.no_proxy)
+            .finish();
+    }
+}
+
+/// Retry policy for idempotent requests (`GET`, `PUT` and `DELETE`).
+///
+/// A request is retried when it can't reach the server or get its response (see
+/// `Error::is_connection_error`), or when the server answers with `429 Too Many Requests`, `500`,
+/// `502`, `503` or `504`. Other errors, e.g. an invalid CA file, are returned at once. The delay between two attempts
+/// doubles each time, starting from `initial_backoff` and capped at `max_backoff`, and is
+/// randomized to avoid every client retrying at the same time. A `Retry-After` header sent by
+/// the server is honoured if it asks for a longer delay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        return RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+        };
+    }
+}
+
+impl RetryPolicy {
+    /// Return a policy retrying 3 times, waiting about 200ms, 400ms then 800ms.
+    pub fn new() -> RetryPolicy {
+        return RetryPolicy::default();
+    }
+
+    /// Set the maximum number of retries after the first attempt.
+    pub fn max_retries(mut self, retries: u32) -> RetryPolicy {
+        self.max_retries = retries;
+        return self;
+    }
+
+    /// Set the delay before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = backoff;
+        return self;
+    }
+
+    /// Set the maximum delay between two attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.max_backoff = backoff;
+        return self;
+    }
+
+    /// Whether a request can be sent again after its `attempt`-th retry.
+    pub(crate) fn allows(&self, method: Method, attempt: u32) -> bool {
+        return method != Method::Post && attempt < self.max_retries;
+    }
+
+    /// Return the delay before retry number `attempt`, starting at 0: a random duration between
+    /// half and all of the exponential backoff, or the `Retry-After` delay asked by the server if
+    /// it is longer, up to `max_backoff`.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        let backoff = match self.initial_backoff.checked_mul(factor) {
+            Some(v) if v < self.max_backoff => v,
+            _ => self.max_backoff,
+        };
+        let half = backoff / 2;
+        let jitter = half.as_nanos() as f64 * random();
+        let delay = half + Duration::from_nanos(jitter as u64);
+        return match retry_after {
+            Some(v) if v > delay => std::cmp::min(v, self.max_backoff),
+            _ => delay,
+        };
+    }
+}
+
+/// Whether a request failing with `status` may succeed if sent again: too many requests, or a
+/// server error which isn't permanent like `501 Not Implemented`.
+pub(crate) fn is_retryable_status(status: u32) -> bool {
+    return matches!(status, 429 | 500 | 502 | 503 | 504);
+}
+
+/// Return the delay asked by the `Retry-After` header of a response, in seconds.
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    return response.headers.iter()
+        .find(|&(name, _)| name.eq_ignore_ascii_case("Retry-After"))
+        .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+}
+
+/// Information about a request about to be retried, given to the `on_retry` hook.
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    /// HTTP method of the request.
+    pub method: Method,
+    /// URL of the request.
+    pub url: &'a str,
+    /// Number of the retry, starting at 1.
+    pub attempt: u32,
+    /// Delay before the request is sent again.
+    pub delay: Duration,
+    /// Status code of the failed attempt, if a response was received.
+    pub status: Option<u32>,
+    /// Error of the failed attempt, if no response was received.
+    pub error: Option<&'a Error>,
+}
+
+/// Return a pseudo-random number in `[0, 1)`, good enough to spread retries over time.
+fn random() -> f64 {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(v) => v.subsec_nanos() as u64,
+        Err(_) => 0,
+    };
+    // xorshift64* on the clock mixed with a counter, so two calls in a row differ.
+    let mut x = (nanos ^ (COUNTER.fetch_add(1, Ordering::Relaxed) as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)) | 1;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    let value = x.wrapping_mul(0x2545_F491_4F6C_DD1D);
+    return (value >> 11) as f64 / (1u64 << 53) as f64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay() {
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(100)).max_backoff(Duration::from_millis(300));
+        for _ in 0..100 {
+            let delay = policy.delay(0, None);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100), "{:?}", delay);
+            let delay = policy.delay(5, None);
+            assert!(delay >= Duration::from_millis(150) && delay <= Duration::from_millis(300), "{:?}", delay);
+        }
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(1))), Duration::from_millis(300));
+        assert!(policy.allows(Method::Get, 2));
+        assert!(!policy.allows(Method::Get, 3));
+        assert!(!policy.allows(Method::Post, 0));
+    }
+
+    #[test]
+    fn tls_from_value() {
+        let tls = TlsConfig::from_value(&json!({
+            "url": "https://rancher.example.com/v2-beta",
+            "cacert": "-----BEGIN CERTIFICATE-----",
+            "clientCert": "/etc/rancher/client.pem",
+            "clientKey": "/etc/rancher/client.key",
+            "pinnedPublicKeys": ["sha256//YhKJKSzoTt2b5FP18fvpHo7fJYqQCjAa3HWY3tvRMwE="]
+        })).unwrap();
+        assert_eq!(tls, TlsConfig::new()
+            .ca_pem(b"-----BEGIN CERTIFICATE-----")
+            .client_cert(Pem::File(PathBuf::from("/etc/rancher/client.pem")), Pem::File(PathBuf::from("/etc/rancher/client.key")))
+            .pin_public_key("sha256//YhKJKSzoTt2b5FP18fvpHo7fJYqQCjAa3HWY3tvRMwE="));
+
+        assert!(TlsConfig::from_value(&json!({"insecure": true})).unwrap().insecure);
+        assert!(TlsConfig::from_value(&json!({"clientCert": "/etc/rancher/client.pem"})).is_err());
+    }
+
+    #[test]
+    fn proxy_from_env() {
+        let var = |name: &str| match name {
+            "HTTPS_PROXY" => Some(String::from("http://proxy.example.com:3128")),
+            "ALL_PROXY" => Some(String::from("socks5h://proxy.example.com:1080")),
+            "no_proxy" => Some(String::from("localhost, .internal,")),
+            _ => None,
+        };
+        let proxy = ProxyConfig::from_vars("https://rancher.example.com/v2-beta", var);
+        assert_eq!(proxy, ProxyConfig::new("http://proxy.example.com:3128").no_proxy("localhost").no_proxy(".internal"));
+        let proxy = ProxyConfig::from_vars("http://rancher.example.com:8080/v2-beta", var);
+        assert_eq!(proxy, ProxyConfig::new("socks5h://proxy.example.com:1080").no_proxy("localhost").no_proxy(".internal"));
+        let proxy = ProxyConfig::from_vars("http://rancher.example.com:8080/v2-beta", |name| match name {
+            "http_proxy" => Some(String::from("http://proxy.example.com:8000")),
+            "HTTPS_PROXY" => Some(String::from("http://proxy.example.com:3128")),
+            _ => None,
+        });
+        assert_eq!(proxy, ProxyConfig::new("http://proxy.example.com:8000"));
+        assert_eq!(ProxyConfig::from_vars("http://rancher.example.com:8080/v2-beta", |_| None), ProxyConfig::direct());
+    }
+
+    #[test]
+    fn proxy_from_value() {
+        let proxy = ProxyConfig::from_value(&json!({
+            "proxy": "http://proxy.example.com:3128",
+            "proxyUsername": "runner",
+            "proxyPassword": "secret",
+            "noProxy": ["localhost", "10.0.0.0/8"]
+        })).unwrap();
+        assert_eq!(proxy, Some(ProxyConfig::new("http://proxy.example.com:3128")
+            .credentials("runner", "secret")
+            .no_proxy("localhost")
+            .no_proxy("10.0.0.0/8")));
+        assert!(!format!("{:?}", proxy).contains("secret"));
+
+        assert_eq!(ProxyConfig::from_value(&json!({"proxy": ""})).unwrap(), Some(ProxyConfig::direct()));
+        assert_eq!(ProxyConfig::from_value(&json!({})).unwrap(), None);
+        assert!(ProxyConfig::from_value(&json!({"proxy": "http://proxy.example.com:3128", "noProxy": 1})).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use serde_json;
+use std::collections::VecDeque;
+use super::Rancher;
+use error::{self, Error};
+use transport::Method;
+use query::Query;
+
+/// Lazy handle on a Rancher collection
+/// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#pagination).
+///
+/// Nothing is requested until the collection is iterated; pages are then fetched one at a time
+/// by following the `pagination.next` link returned by Rancher.
+///
+/// ```no_run
+/// # use rancher::{Rancher, Container, Query};
+/// # let rancher = Rancher::new(String::from("http://rancher.test"), String::from("access"), String::from("secret"));
+/// for container in rancher.collection::<Container>("1a5").query(&Query::new().eq("state", "running")).page_size(50).iter() {
+///     let container = container.unwrap();
+///     println!("{} -> {}", container.id, container.name.unwrap_or_default());
+/// }
+/// ```
+pub struct Collection<'a, T> {
+    rancher: &'a Rancher,
+    path: String,
+    query: Query,
+    parse: fn(&serde_json::Value) -> Result<T, Error>,
+}
+
+impl<'a, T> Collection<'a, T> {
+    /// Return a collection reading `path` and building items with `parse`.
+    pub(crate) fn new(rancher: &'a Rancher, path: String, parse: fn(&serde_json::Value) -> Result<T, Error>) -> Collection<'a, T> {
+        return Collection {
+            rancher,
+            path,
+            query: Query::new(),
+            parse,
+        };
+    }
+
+    /// Filter and sort the collection on the server side.
+    pub fn query(mut self, query: &Query) -> Collection<'a, T> {
+        self.query = query.clone();
+        return self;
+    }
+
+    /// Set the number of items requested per page. Rancher defaults to 100.
+    pub fn page_size(mut self, size: u32) -> Collection<'a, T> {
+        self.query = self.query.limit(size);
+        return self;
+    }
+
+    /// Return the first item of the collection, fetching a single item from the server.
+    pub fn first(self) -> Result<Option<T>, Error> {
+        return match self.page_size(1).iter().next() {
+            Some(Ok(item)) => Ok(Some(item)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        };
+    }
+
+    /// Return an iterator over every item of the collection, fetching pages lazily.
+    pub fn iter(self) -> Iter<'a, T> {
+        let path = self.query.append_to(&self.path);
+        return Iter {
+            rancher: self.rancher,
+            next: Some(Page::Path(path)),
+            items: VecDeque::new(),
+            parse: self.parse,
+        };
+    }
+}
+
+impl<'a, T> IntoIterator for Collection<'a, T> {
+    type Item = Result<T, Error>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        return self.iter();
+    }
+}
+
+/// Location of the next page to fetch.
+enum Page {
+    /// Path relative to the Rancher API URL, for the first page.
+    Path(String),
+    /// Absolute URL taken from `pagination.next`.
+    Url(String),
+}
+
+/// Iterator over the items of a `Collection`.
+///
+/// It yields an error and stops if a page can't be fetched or decoded.
+pub struct Iter<'a, T> {
+    rancher: &'a Rancher,
+    next: Option<Page>,
+    items: VecDeque<serde_json::Value>,
+    parse: fn(&serde_json::Value) -> Result<T, Error>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// Fetch the next page and queue its items.
+    fn fetch(&mut self, page: Page) -> Result<(), Error> {
+        let results = match page {
+            Page::Path(path) => self.rancher.call_api(&path)?,
+            Page::Url(url) => self.rancher.request_url(Method::Get, &url, None)?,
+        };
+        self.items.extend(error::collection_data(&results)?.iter().cloned());
+        self.next = results["pagination"]["next"].as_str().map(|v| Page::Url(v.to_string()));
+        return Ok(());
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        while self.items.is_empty() {
+            let page = self.next.take()?;
+            if let Err(e) = self.fetch(page) {
+                self.items.clear();
+                return Some(Err(e));
+            }
+        }
+        return self.items.pop_front().map(|item| (self.parse)(&item));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use transport::MockTransport;
+    use test_util;
+
+    fn id(value: &serde_json::Value) -> Result<String, Error> {
+        return value["id"].as_str().map(|v| v.to_string())
+            .ok_or_else(|| Error::Decode(String::from("object without `id` field")));
+    }
+
+    #[test]
+    fn follow_next_page() {
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Get, "/projects/1a5/containers?limit=2", 200, r#"{
+                "data": [{"id": "1i1"}, {"id": "1i2"}],
+                "pagination": {"next": "http://rancher.test/projects/1a5/containers?limit=2&marker=m2"}
+            }"#)
+            .on(Method::Get, "/projects/1a5/containers?limit=2&marker=m2", 200, r#"{
+                "data": [{"id": "1i3"}],
+                "pagination": {"next": null}
+            }"#));
+        let rancher = test_util::rancher(transport.clone());
+        let ids: Result<Vec<String>, Error> = Collection::new(&rancher, String::from("/projects/1a5/containers"), id)
+            .page_size(2)
+            .iter()
+            .collect();
+
+        assert_eq!(ids.unwrap(), vec!["1i1", "1i2", "1i3"]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let transport = MockTransport::new()
+            .on(Method::Get, "/projects/1a5/containers", 200, r#"{
+                "data": [{"id": "1i1"}],
+                "pagination": {"next": "http://rancher.test/projects/1a5/containers?marker=m2"}
+            }"#);
+        let rancher = test_util::rancher(transport);
+        let mut iter = Collection::new(&rancher, String::from("/projects/1a5/containers"), id).iter();
+
+        assert_eq!(iter.next().unwrap().unwrap(), "1i1");
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,470 @@
+use std;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, prelude::*};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use serde_json::{self, Map};
+use error::Error;
+
+/// Environment variable overriding the path of the configuration file.
+pub const CONFIG_VAR: &str = "RANCHER_CONFIG";
+
+/// Name given to the only profile of a file written by the rancher CLI 1.x.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Number of temporary files created by `Config::save_to` in this process.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Settings of one Rancher server.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// URL of the Rancher API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Access key of the Rancher API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key: Option<String>,
+    /// Secret key of the Rancher API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key: Option<String>,
+    /// Default environment, by ID or name.
+    #[serde(default, alias = "project", skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Every other field, e.g. the TLS and proxy settings.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
+}
+
+impl Profile {
+    /// Check that the URL and the credentials are set.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        let token = self.extra.get("tokenKey").and_then(|v| v.as_str()).is_some_and(|v| v.contains(':'));
+        let missing = if self.url.is_none() {
+            "url"
+        } else if self.access_key.is_none() && !token {
+            "accessKey"
+        } else if self.secret_key.is_none() && !token {
+            "secretKey"
+        } else {
+            return Ok(());
+        };
+        return Err(format!("missing `{}`", missing));
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_struct("Profile")
+            .field("url", &self.url)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &self.secret_key.as_ref().map(|_| "<redacted>"))
+            .field("environment", &self.environment)
+            .finish();
+    }
+}
+
+/// Profiles read from a configuration file.
+///
+/// The format is the one of the official rancher CLI: `~/.rancher/cli.json`, or the file named by
+/// `RANCHER_CONFIG`, lists profiles under `Servers` and names the default one in
+/// `CurrentServer`:
+///
+/// ```json
+/// {
+///     "Servers": {
+///         "production": {"url": "https://rancher.example.com/v2-beta", "accessKey": "...", "secretKey": "..."},
+///         "lab": {"url": "https://rancher.lab/v2-beta", "accessKey": "...", "secretKey": "...", "insecure": true}
+///     },
+///     "CurrentServer": "production"
+/// }
+/// ```
+///
+/// A file holding the fields of a single server, as written by the rancher CLI 1.x, is read as
+/// one profile named `default`, and written back the same way as long as it's the only one.
+///
+/// Profiles can be changed and saved back:
+///
+/// ```no_run
+/// # use rancher::Config;
+/// # fn main() -> Result<(), rancher::Error> {
+/// let mut config = Config::load()?;
+/// config.rotate_keys("production", "new-access", "new-secret")?;
+/// config.save()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    path: Option<PathBuf>,
+    profiles: BTreeMap<String, Profile>,
+    current: Option<String>,
+    single: bool,
+    extra: Map<String, serde_json::Value>,
+}
+
+impl Config {
+    /// Return a configuration without any profile.
+    pub fn new() -> Config {
+        return Config::default();
+    }
+
+    /// Return the path of the configuration file: `RANCHER_CONFIG` if set, `~/.rancher/cli.json`
+    /// otherwise.
+    pub fn default_path() -> Result<PathBuf, Error> {
+        if let Some(path) = std::env::var_os(CONFIG_VAR).filter(|v| !v.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+        return match std::env::home_dir() {
+            Some(home) => Ok(home.join(".rancher").join("cli.json")),
+            None => Err(Error::Config(String::from("can't find the home directory"))),
+        };
+    }
+
+    /// Read the configuration file at `default_path`.
+    pub fn load() -> Result<Config, Error> {
+        return Config::load_from(Config::default_path()?);
+    }
+
+    /// Read the configuration file at `default_path`, or return `None` if `RANCHER_CONFIG` isn't
+    /// set and `~/.rancher/cli.json` doesn't exist. A file named by `RANCHER_CONFIG` must exist.
+    pub(crate) fn load_default() -> Result<Option<Config>, Error> {
+        let required = std::env::var_os(CONFIG_VAR).is_some_and(|v| !v.is_empty());
+        return Config::load_if_present(Config::default_path()?, required);
+    }
+
+    /// Read the configuration file at `path`, or return `None` if it doesn't exist and isn't
+    /// `required`.
+    fn load_if_present(path: PathBuf, required: bool) -> Result<Option<Config>, Error> {
+        if !required && !path.is_file() {
+            return Ok(None);
+        }
+        return Config::load_from(path).map(Some);
+    }
+
+    /// Read the configuration file at `path`.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Config, Error> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        match fs::File::open(path) {
+            Ok(mut file) => file.read_to_string(&mut content)?,
+            Err(e) => return Err(Error::Config(format!("can't read {}: {}", path.display(), e))),
+        };
+        let value: serde_json::Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => return Err(Error::Config(format!("invalid JSON in {}: {}", path.display(), e))),
+        };
+        let mut config = Config::from_value(&value).map_err(|e| Error::Config(format!("{} in {}", e, path.display())))?;
+        config.path = Some(path.to_path_buf());
+        return Ok(config);
+    }
+
+    /// Read the profiles of a configuration file.
+    pub(crate) fn from_value(value: &serde_json::Value) -> Result<Config, String> {
+        if !value.is_object() {
+            return Err(String::from("expected a JSON object"));
+        }
+        let mut config = Config::new();
+        if value["Servers"].is_null() {
+            let profile = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+            config.profiles.insert(String::from(DEFAULT_PROFILE), profile);
+            config.current = Some(String::from(DEFAULT_PROFILE));
+            config.single = true;
+            return Ok(config);
+        }
+        let servers = match value["Servers"].as_object() {
+            Some(v) => v,
+            None => return Err(String::from("`Servers` must be an object")),
+        };
+        for (name, server) in servers {
+            let profile = match serde_json::from_value(server.clone()) {
+                Ok(v) => v,
+                Err(e) => return Err(format!("invalid profile `{}`: {}", name, e)),
+            };
+            config.profiles.insert(name.clone(), profile);
+        }
+        config.current = value["CurrentServer"].as_str().filter(|v| !v.is_empty()).map(|v| v.to_string());
+        if let Some(object) = value.as_object() {
+            config.extra = object.iter()
+                .filter(|&(key, _)| key != "Servers" && key != "CurrentServer")
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+        }
+        return Ok(config);
+    }
+
+    /// Return the content of the configuration file.
+    pub(crate) fn to_value(&self) -> Result<serde_json::Value, Error> {
+        if self.single && self.names() == [DEFAULT_PROFILE] && self.current.as_deref().unwrap_or(DEFAULT_PROFILE) == DEFAULT_PROFILE {
+            return Ok(serde_json::to_value(&self.profiles[DEFAULT_PROFILE])?);
+        }
+        let mut object = self.extra.clone();
+        object.insert(String::from("Servers"), serde_json::to_value(&self.profiles)?);
+        if let Some(ref current) = self.current {
+            object.insert(String::from("CurrentServer"), serde_json::Value::String(current.clone()));
+        }
+        return Ok(serde_json::Value::Object(object));
+    }
+
+    /// Write the configuration back to the file it was read from, or to `default_path` for a new
+    /// one, see `save_to`.
+    pub fn save(&self) -> Result<(), Error> {
+        return match self.path {
+            Some(ref path) => self.save_to(path),
+            None => self.save_to(Config::default_path()?),
+        };
+    }
+
+    /// Write the configuration to `path`.
+    ///
+    /// The file is readable by its owner only, as it holds secret keys, and replaced atomically:
+    /// the content is written to a temporary file next to it which is then renamed, so readers
+    /// never see a partial file.
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(&self.to_value()?)?;
+        let dir = match path.parent() {
+            Some(v) if !v.as_os_str().is_empty() => v,
+            _ => Path::new("."),
+        };
+        create_private_dir(dir)?;
+        let file_name = match path.file_name() {
+            Some(v) => v.to_string_lossy(),
+            None => return Err(Error::Config(format!("invalid config file path {}", path.display()))),
+        };
+        let (tmp, mut file) = loop {
+            // Unique within the process, and retried if a crashed run with the same PID left it.
+            let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let tmp = dir.join(format!(".{}.{}.{}.tmp", file_name, std::process::id(), n));
+            match create_private_file(&tmp) {
+                Ok(file) => break (tmp, file),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(Error::Io(e)),
+            }
+        };
+        let result = file.write_all(content.as_bytes())
+            .and_then(|_| file.sync_all())
+            .and_then(|_| fs::rename(&tmp, path));
+        if let Err(e) = result {
+            // Only this call created `tmp`, so no other writer loses its file.
+            let _ = fs::remove_file(&tmp);
+            return Err(Error::Io(e));
+        }
+        return Ok(());
+    }
+
+    /// Return the path the configuration was read from.
+    pub fn path(&self) -> Option<&Path> {
+        return self.path.as_deref();
+    }
+
+    /// Return the names of the profiles, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        return self.profiles.keys().map(|v| v.as_str()).collect();
+    }
+
+    /// Return the profile named exactly `name`.
+    pub fn profile(&self, name: &str) -> Result<&Profile, Error> {
+        return match self.profiles.get(name) {
+            Some(v) => Ok(v),
+            None => Err(self.no_profile(name)),
+        };
+    }
+
+    /// Return the name of the default profile: `CurrentServer`, or the only profile.
+    pub fn default_name(&self) -> Option<&str> {
+        if let Some(ref current) = self.current {
+            return Some(current);
+        }
+        if self.profiles.len() == 1 {
+            return self.profiles.keys().next().map(|v| v.as_str());
+        }
+        return None;
+    }
+
+    /// Return the default profile, see `default_name`.
+    pub fn default_profile(&self) -> Result<&Profile, Error> {
+        return match self.default_name() {
+            Some(name) => self.profile(name),
+            None => Err(Error::Config(format!("no default profile{}, set `CurrentServer`", self.origin()))),
+        };
+    }
+
+    /// Return the profile named exactly `name`, to change it.
+    pub fn profile_mut(&mut self, name: &str) -> Result<&mut Profile, Error> {
+        if !self.profiles.contains_key(name) {
+            return Err(self.no_profile(name));
+        }
+        return Ok(self.profiles.get_mut(name).unwrap());
+    }
+
+    /// Add a profile, or replace the one with the same name.
+    pub fn set_profile(&mut self, name: &str, profile: Profile) {
+        self.profiles.insert(name.to_string(), profile);
+    }
+
+    /// Remove a profile and return it. It's no longer the default one if it was.
+    pub fn remove_profile(&mut self, name: &str) -> Option<Profile> {
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        return self.profiles.remove(name);
+    }
+
+    /// Make the profile `name` the default one.
+    pub fn set_default(&mut self, name: &str) -> Result<(), Error> {
+        self.profile(name)?;
+        self.current = Some(name.to_string());
+        return Ok(());
+    }
+
+    /// Set the default environment, by ID or name, of the profile `name`.
+    pub fn set_environment(&mut self, name: &str, environment: &str) -> Result<(), Error> {
+        self.profile_mut(name)?.environment = Some(environment.to_string());
+        return Ok(());
+    }
+
+    /// Replace the access and secret keys of the profile `name`.
+    pub fn rotate_keys(&mut self, name: &str, access_key: &str, secret_key: &str) -> Result<(), Error> {
+        let profile = self.profile_mut(name)?;
+        profile.access_key = Some(access_key.to_string());
+        profile.secret_key = Some(secret_key.to_string());
+        // The token key of the official CLI holds the same credentials.
+        profile.extra.remove("tokenKey");
+        return Ok(());
+    }
+
+    /// Return the profile named `name` after checking its URL and credentials are set.
+    pub(crate) fn complete_profile(&self, name: &str) -> Result<&Profile, Error> {
+        let profile = self.profile(name)?;
+        if let Err(e) = profile.check() {
+            return Err(Error::Config(format!("profile `{}`{}: {}", name, self.origin(), e)));
+        }
+        return Ok(profile);
+    }
+
+    /// Return the error of a missing profile.
+    fn no_profile(&self, name: &str) -> Error {
+        return Error::Config(format!("no profile named `{}`{}, available profiles: [{}]",
+                                     name, self.origin(), self.names().join(", ")));
+    }
+
+    /// Return ` in <path>` for error messages, if the configuration was read from a file.
+    fn origin(&self) -> String {
+        return match self.path {
+            Some(ref path) => format!(" in {}", path.display()),
+            None => String::new(),
+        };
+    }
+}
+
+/// Create `dir` and its parents if missing, readable by their owner only.
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    return builder.create(dir);
+}
+
+/// Create the file `path`, readable by its owner only, failing if it already exists.
+fn create_private_file(path: &Path) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    return options.open(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles() {
+        let config = Config::from_value(&json!({
+            "Servers": {
+                "production": {"url": "https://rancher.example.com/v2-beta", "accessKey": "access", "secretKey": "secret"},
+                "lab": {"url": "https://rancher.lab/v2-beta", "tokenKey": "access:secret", "project": "1a5", "insecure": true}
+            },
+            "CurrentServer": "production"
+        })).unwrap();
+        assert_eq!(config.names(), vec!["lab", "production"]);
+        assert_eq!(config.default_name(), Some("production"));
+        assert_eq!(config.default_profile().unwrap().access_key, Some(String::from("access")));
+
+        let lab = config.complete_profile("lab").unwrap();
+        assert_eq!(lab.environment, Some(String::from("1a5")));
+        assert_eq!(lab.extra["insecure"], true);
+        assert!(!format!("{:?}", config).contains("\"secret\""));
+
+        // Names are matched exactly.
+        match config.profile("prod") {
+            Err(Error::Config(msg)) => assert!(msg.contains("[lab, production]"), "{}", msg),
+            other => panic!("Expected Config, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn single_server() {
+        let config = Config::from_value(&json!({"url": "http://rancher.test/v2-beta", "accessKey": "access"})).unwrap();
+        assert_eq!(config.default_name(), Some(DEFAULT_PROFILE));
+        match config.complete_profile(DEFAULT_PROFILE) {
+            Err(Error::Config(msg)) => assert_eq!(msg, "profile `default`: missing `secretKey`"),
+            other => panic!("Expected Config, got {:?}", other)
+        }
+        assert!(Config::from_value(&json!({"Servers": []})).is_err());
+        assert!(Config::from_value(&json!([])).is_err());
+    }
+
+    #[test]
+    fn load_default() {
+        let path = std::env::temp_dir().join(format!("rancher-missing-{}", std::process::id())).join("cli.json");
+        assert_eq!(Config::load_if_present(path.clone(), false).unwrap(), None);
+        match Config::load_if_present(path.clone(), true) {
+            Err(Error::Config(msg)) => assert!(msg.starts_with(&format!("can't read {}", path.display())), "{}", msg),
+            other => panic!("Expected Config, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn save() {
+        let dir = std::env::temp_dir().join(format!("rancher-config-{}", std::process::id()));
+        let path = dir.join("cli.json");
+
+        let mut config = Config::from_value(&json!({"url": "http://rancher.test/v2-beta", "accessKey": "a", "secretKey": "s"})).unwrap();
+        config.save_to(&path).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().to_value().unwrap()["url"], "http://rancher.test/v2-beta");
+
+        config.rotate_keys(DEFAULT_PROFILE, "access", "secret").unwrap();
+        config.set_profile("lab", Profile { url: Some(String::from("http://rancher.lab/v2-beta")), ..Profile::default() });
+        config.set_environment("lab", "1a5").unwrap();
+        config.set_default("lab").unwrap();
+        assert!(config.set_default("staging").is_err());
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(loaded.names(), vec![DEFAULT_PROFILE, "lab"]);
+        assert_eq!(loaded.default_name(), Some("lab"));
+        assert_eq!(loaded.profile("lab").unwrap().environment, Some(String::from("1a5")));
+        assert_eq!(loaded.profile(DEFAULT_PROFILE).unwrap().secret_key, Some(String::from("secret")));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // A temporary file left by a crashed run is neither reused nor removed.
+        let stale = dir.join(format!(".cli.json.{}.{}.tmp", std::process::id(), TMP_COUNTER.load(Ordering::Relaxed)));
+        fs::write(&stale, "{}").unwrap();
+        config.save_to(&path).unwrap();
+        assert_eq!(fs::read_to_string(&stale).unwrap(), "{}");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,200 +1,325 @@
-use serde_json;
+use serde_json::{self, Map};
 use super::Rancher;
+use std::time::Duration;
+use std::collections::HashMap;
+use error::Error;
+use model;
+use state::{ContainerState, HealthState, AllocationState, Transitioning};
+use resource::Resource;
+
+/// Duration between two requests while waiting for an action to complete.
+const POLL_INTERVAL_MS: u64 = 1000;
 
 /// Structure that contains all data for a Rancher Container (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/container/)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Container {
-    /// Container unique ID
+    /// Unique ID.
     pub id: String,
-    /// Container name
-    pub name: String,
+    /// Name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kind of resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
     /// ID of the host on which the container is located.
-    pub host_id: String,
-    /// No idea what is for.
-    pub requested_host_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_id: Option<String>,
+    /// ID of the host requested when scheduling the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_host_id: Option<String>,
     /// Result of healthcheck on the container
-    pub health_state: String,
-    /// Native container
-    pub native_container: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_state: Option<HealthState>,
+    /// Whether the container was created outside of Rancher.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_container: Option<bool>,
     /// The unique identifier of the associated service.
-    pub service_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
     /// IDs of services associated with the container.
+    #[serde(default, deserialize_with = "model::null_as_default")]
     pub service_ids: Vec<String>,
+    /// ID of the stack of the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_id: Option<String>,
     /// IP address of the container.
-    pub primary_ip_address: String,
-    /// _
-    pub allocation_state: String,
-    /// Container state. It could be `stopped`, `running`, `started`.
-    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_ip_address: Option<String>,
+    /// Allocation state of the container on its host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allocation_state: Option<AllocationState>,
+    /// Container state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ContainerState>,
+    /// Image of the container, e.g. `docker:nginx:latest`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_uuid: Option<String>,
+    /// Command of the container.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub command: Vec<String>,
+    /// Environment variables of the container.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub environment: HashMap<String, String>,
+    /// Labels.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub labels: HashMap<String, String>,
+    /// Published ports, e.g. `8080:80/tcp`.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub ports: Vec<String>,
+    /// Network mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+    /// Number of times the container was started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_count: Option<u64>,
+    /// Date of the first start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_running: Option<String>,
+    /// UUID of the deployment unit of the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment_unit_uuid: Option<String>,
+    /// ID of the container in Docker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    /// ID of the account owning the resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Creation date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Removal date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<String>,
+    /// Universally unique ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Whether an asynchronous operation is running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning: Option<Transitioning>,
+    /// Message of the running operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_message: Option<String>,
+    /// Progress of the running operation, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_progress: Option<u64>,
+    /// Links to the resource itself and its related resources.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub links: HashMap<String, String>,
+    /// Actions currently allowed on the resource, with their URL.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub actions: HashMap<String, String>,
+    /// Every other field returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
 }
 
 impl Container {
+    /// Restart a container based on an environment id and a container id.
+    ///
+    /// # Arguments
+    ///
+    /// * `rancher` - A Rancher struct.
+    /// * `environment_id` - An environment id.
+    /// * `container_id` - A container id.
+    ///
+    pub fn restart(rancher: &Rancher, environment_id: &str, container_id: &str) -> Result<Container, Error> {
+        return Container::action(rancher, environment_id, container_id, "restart");
+    }
 
-    /// Get all containers from an environment.
+    /// Start a container based on an environment id and a container id.
     ///
     /// # Arguments
     ///
-    /// * `rancher` - A mutable Rancher struct.
+    /// * `rancher` - A Rancher struct.
     /// * `environment_id` - An environment id.
+    /// * `container_id` - A container id.
     ///
-    pub fn get_all(rancher: &mut Rancher, environment_id: &str) -> Result<Vec<Container>, &'static str> {
-        let mut data: Vec<Container> = Vec::new();
-        let path = format!("/projects/{}/containers?limit=100000", environment_id);
-        let results = rancher.call_api(&path).unwrap();
-        let containers = results["data"].as_array().unwrap();
-        for container in containers {
-            let mut services: Vec<String> = Vec::new();
-            for service in container["serviceIds"].as_array().unwrap_or(&mut Vec::new()) {
-                services.push(service.as_str().unwrap_or("").to_string());
-            }
-            data.push(Container{
-                id:         container["id"].as_str().unwrap().to_string(),
-                name:       container["name"].as_str().unwrap_or("").to_string(),
-                host_id:    container["hostId"].as_str().unwrap_or("").to_string(),
-                requested_host_id:    container["requestedHostId"].as_str().unwrap_or("").to_string(),
-                health_state: container["healthState"].as_str().unwrap_or("").to_string(),
-                native_container: container["nativeContainer"].as_str().unwrap_or("").to_string(),
-                service_id: container["serviceId"].as_str().unwrap_or("").to_string(),
-                service_ids: services,
-                primary_ip_address: container["primaryIpAddress"].as_str().unwrap_or("").to_string(),
-                allocation_state: container["allocationState"].as_str().unwrap_or("").to_string(),
-                state: container["state"].as_str().unwrap_or("").to_string(),
-            });
-        }
-        return Ok(data);
+    pub fn start(rancher: &Rancher, environment_id: &str, container_id: &str) -> Result<Container, Error> {
+        return Container::action(rancher, environment_id, container_id, "start");
     }
 
-    /// Get one container based on an environment and a pattern that should match with a container id or a container
-    /// name.
+    /// Stop a container based on an environment id and a container id.
     ///
     /// # Arguments
     ///
-    /// * `rancher` - A mutable Rancher struct.
+    /// * `rancher` - A Rancher struct.
     /// * `environment_id` - An environment id.
-    /// * `pattern` - A string that should match with a container id or a container name.
-    ///
-    pub fn get_one(rancher: &mut Rancher, environment_id: &str, pattern: &str) -> Result<Container, &'static str> {
-        let mut name: String;
-        let path = format!("/projects/{}/containers?limit=100000", environment_id);
-        let results: serde_json::Value = rancher.call_api(&path).unwrap();
-        let containers = results["data"].as_array().unwrap();
-        for container in containers {
-            let mut services: Vec<String> = Vec::new();
-            for service in container["serviceIds"].as_array().unwrap_or(&mut Vec::new()) {
-                services.push(service.as_str().unwrap_or("").to_string());
-            }
-            name = match container["name"].as_str() {
-                Some(v) => v.to_string(),
-                None    => String::from("")
-            };
-            if pattern == container["id"].as_str().unwrap() || pattern == name {
-                return Ok(Container{
-                    id:         container["id"].as_str().unwrap().to_string(),
-                    name:       container["name"].as_str().unwrap_or("").to_string(),
-                    host_id:    container["hostId"].as_str().unwrap_or("").to_string(),
-                    requested_host_id:    container["requestedHostId"].as_str().unwrap_or("").to_string(),
-                    health_state: container["healthState"].as_str().unwrap_or("").to_string(),
-                    native_container: container["nativeContainer"].as_str().unwrap_or("").to_string(),
-                    service_id: container["serviceId"].as_str().unwrap_or("").to_string(),
-                    service_ids: services,
-                    primary_ip_address: container["ip"].as_str().unwrap_or("").to_string(),
-                    allocation_state: container["allocationState"].as_str().unwrap_or("").to_string(),
-                    state: container["state"].as_str().unwrap_or("").to_string(),
-                });
-            }
-        }
-        return Err("No container found.");
+    /// * `container_id` - A container id.
+    ///
+    pub fn stop(rancher: &Rancher, environment_id: &str, container_id: &str) -> Result<Container, Error> {
+        return Container::action(rancher, environment_id, container_id, "stop");
     }
 
-    /// Restart a container based on an environment id and a container id.
+    /// Restart a container based on an environment id and a container id, and wait until it is running again.
     ///
     /// # Arguments
     ///
-    /// * `rancher` - A mutable Rancher struct.
+    /// * `rancher` - A Rancher struct.
     /// * `environment_id` - An environment id.
     /// * `container_id` - A container id.
+    /// * `timeout` - The maximum duration to wait.
     ///
-    pub fn restart(rancher: &mut Rancher, environment_id: &str, container_id: &str) -> bool {
-        let path = format!("/projects/{}/containers/{}?action=restart", environment_id, container_id);
-        match rancher.post_api_without_data(&path) {
-            Ok(_) => {
-                return true;
-            },
-            Err(_) => {
-                return false;
-            }
-        }
+    pub fn restart_and_wait(rancher: &Rancher, environment_id: &str, container_id: &str, timeout: Duration) -> Result<Container, Error> {
+        let container = Container::restart(rancher, environment_id, container_id)?;
+        return rancher.wait_until(&container, |c| c.is_settled(ContainerState::Running), timeout, Duration::from_millis(POLL_INTERVAL_MS));
     }
 
-    /// Start a container based on an environment id and a container id.
+    /// Start a container based on an environment id and a container id, and wait until it is running.
     ///
     /// # Arguments
     ///
-    /// * `rancher` - A mutable Rancher struct.
+    /// * `rancher` - A Rancher struct.
     /// * `environment_id` - An environment id.
     /// * `container_id` - A container id.
+    /// * `timeout` - The maximum duration to wait.
     ///
-    pub fn start(rancher: &mut Rancher, environment_id: &str, container_id: &str) -> bool {
-        let path = format!("/projects/{}/containers/{}?action=start", environment_id, container_id);
-        match rancher.post_api_without_data(&path) {
-            Ok(_) => {
-                return true;
-            },
-            Err(_) => {
-                return false;
-            }
-        }
+    pub fn start_and_wait(rancher: &Rancher, environment_id: &str, container_id: &str, timeout: Duration) -> Result<Container, Error> {
+        let container = Container::start(rancher, environment_id, container_id)?;
+        return rancher.wait_until(&container, |c| c.is_settled(ContainerState::Running), timeout, Duration::from_millis(POLL_INTERVAL_MS));
     }
 
-    /// Stop a container based on an environment id and a container id.
+    /// Stop a container based on an environment id and a container id, and wait until it is stopped.
     ///
     /// # Arguments
     ///
-    /// * `rancher` - A mutable Rancher struct.
+    /// * `rancher` - A Rancher struct.
     /// * `environment_id` - An environment id.
     /// * `container_id` - A container id.
+    /// * `timeout` - The maximum duration to wait.
     ///
-    pub fn stop(rancher: &mut Rancher, environment_id: &str, container_id: &str) -> bool {
-        let path = format!("/projects/{}/containers/{}?action=stop", environment_id, container_id);
-        match rancher.post_api_without_data(&path) {
-            Ok(_) => {
-                return true;
-            },
-            Err(_) => {
-                return false;
-            }
-        }
+    pub fn stop_and_wait(rancher: &Rancher, environment_id: &str, container_id: &str, timeout: Duration) -> Result<Container, Error> {
+        let container = Container::stop(rancher, environment_id, container_id)?;
+        return rancher.wait_until(&container, |c| c.is_settled(ContainerState::Stopped), timeout, Duration::from_millis(POLL_INTERVAL_MS));
     }
 
+    /// Invoke `action` on a container based on an environment id and a container id, if Rancher
+    /// currently allows it.
+    fn action(rancher: &Rancher, environment_id: &str, container_id: &str, action: &str) -> Result<Container, Error> {
+        let container: Container = rancher.get(environment_id, container_id)?;
+        let result = rancher.invoke(&container, action, None)?;
+        return Container::parse(&result);
+    }
+
+    /// Whether the container is in `state` and no operation is running on it.
+    fn is_settled(&self, state: ContainerState) -> bool {
+        return self.state == Some(state) && self.transitioning != Some(Transitioning::Yes);
+    }
 }
 
-/// This trait allows you to call Container's functions with `rancher.restart_container(.....)`
-pub trait ContainerTrait {
-    /// Get all containers from an environment.
-    fn get_containers(&mut self, environment_id: &str) -> Result<Vec<Container>, &'static str>;
-    /// Get one container based on an environment and a pattern that should match with a container id or a container name.
-    fn get_container(&mut self, environment_id: &str, pattern: &str) -> Result<Container, &'static str>;
-    /// Restart a container based on an environment id and a container id.
-    fn restart_container(&mut self, environment_id: &str, container_id: &str) -> bool;
-    /// Start a container based on an environment id and a container id.
-    fn start_container(&mut self, environment_id: &str, container_id: &str) -> bool;
-    /// Stop a container based on an environment id and a container id.
-    fn stop_container(&mut self, environment_id: &str, container_id: &str) -> bool;
+impl Resource for Container {
+    type Parent = str;
+    type Id = str;
+
+    const TYPE: &'static str = "container";
+
+    fn collection_path(environment_id: &str) -> String {
+        return format!("/projects/{}/containers", environment_id);
+    }
 }
 
-impl ContainerTrait for Rancher {
-    fn get_containers(&mut self, environment_id: &str) -> Result<Vec<Container>, &'static str> {
-        return Container::get_all(self, environment_id);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transport::{Method, MockTransport};
+    use test_util;
+
+    fn rancher(state: &str) -> Rancher {
+        let action_response = r#"{
+            "id": "1i10",
+            "name": "web",
+            "state": "starting",
+            "transitioning": "yes",
+            "links": {"self": "http://rancher.test/projects/1a5/containers/1i10"}
+        }"#;
+        let container = r#"{
+            "id": "1i10",
+            "name": "web",
+            "state": "stopped",
+            "transitioning": "no",
+            "links": {"self": "http://rancher.test/projects/1a5/containers/1i10"},
+            "actions": {
+                "start": "http://rancher.test/projects/1a5/containers/1i10?action=start",
+                "remove": "http://rancher.test/projects/1a5/containers/1i10?action=remove"
+            }
+        }"#;
+        let transport = MockTransport::new()
+            .on(Method::Post, "/projects/1a5/containers/1i10?action=start", 200, action_response)
+            .on(Method::Get, "/projects/1a5/containers/1i10", 200, container)
+            .on(Method::Get, "/projects/1a5/containers/1i10", 200, state);
+        return test_util::rancher(transport);
+    }
+
+    #[test]
+    fn start_and_wait() {
+        let rancher = rancher(r#"{
+            "id": "1i10",
+            "name": "web",
+            "state": "running",
+            "transitioning": "no",
+            "links": {"self": "http://rancher.test/projects/1a5/containers/1i10"}
+        }"#);
+        let container = Container::start_and_wait(&rancher, "1a5", "1i10", Duration::from_secs(5)).unwrap();
+        assert_eq!(container.state, Some(ContainerState::Running));
     }
-    fn get_container(&mut self, environment_id: &str, pattern: &str) -> Result<Container, &'static str> {
-        return Container::get_one(self, environment_id, pattern);
+
+    #[test]
+    fn watch() {
+        let rancher = rancher(r#"{
+            "id": "1i10",
+            "name": "web",
+            "state": "running",
+            "transitioning": "no",
+            "links": {"self": "http://rancher.test/projects/1a5/containers/1i10"}
+        }"#);
+        let container = rancher.watch::<Container, _>("1a5", "1i10", |c| c.state == Some(ContainerState::Running),
+                                                       Duration::from_secs(5), Duration::from_millis(5)).unwrap();
+        assert_eq!(container.name, Some(String::from("web")));
     }
-    fn restart_container(&mut self, environment_id: &str, container_id: &str) -> bool {
-        return Container::restart(self, environment_id, container_id);
+
+    #[test]
+    fn start_and_wait_error() {
+        let rancher = rancher(r#"{
+            "id": "1i10",
+            "name": "web",
+            "state": "stopped",
+            "transitioning": "error",
+            "transitioningMessage": "Failed to start: port already in use",
+            "links": {"self": "http://rancher.test/projects/1a5/containers/1i10"}
+        }"#);
+        match Container::start_and_wait(&rancher, "1a5", "1i10", Duration::from_secs(5)) {
+            Err(Error::Transition(msg)) => assert!(msg.contains("port already in use"), "{}", msg),
+            other => panic!("Expected a transition error, got {:?}", other)
+        }
     }
-    fn start_container(&mut self, environment_id: &str, container_id: &str) -> bool {
-        return Container::start(self, environment_id, container_id);
+
+    #[test]
+    fn wait_until_timeout() {
+        let rancher = rancher(r#"{
+            "id": "1i10",
+            "name": "web",
+            "state": "starting",
+            "transitioning": "yes",
+            "links": {"self": "http://rancher.test/projects/1a5/containers/1i10"}
+        }"#);
+        let container = Container::start(&rancher, "1a5", "1i10").unwrap();
+        let result = rancher.wait_until(&container, |c: &Container| c.state == Some(ContainerState::Running),
+                                        Duration::from_millis(20), Duration::from_millis(5));
+        match result {
+            Err(Error::Timeout(_)) => {},
+            other => panic!("Expected a timeout, got {:?}", other)
+        }
     }
-    fn stop_container(&mut self, environment_id: &str, container_id: &str) -> bool {
-        return Container::restart(self, environment_id, container_id);
+
+    #[test]
+    fn unavailable_action() {
+        let rancher = rancher("{}");
+        match Container::stop(&rancher, "1a5", "1i10") {
+            Err(Error::UnavailableAction(msg)) => assert!(msg.contains("[remove, start]"), "{}", msg),
+            other => panic!("Expected an unavailable action, got {:?}", other)
+        }
     }
- }
+}
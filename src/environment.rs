@@ -1,64 +1,84 @@
-use serde_json;
-use super::Rancher;
+use serde_json::{self, Map};
+use std::collections::HashMap;
+use model;
+use state::{HealthState, Transitioning};
+use resource::Resource;
 
 /// Struct that contains all data for a Rancher Environment
 /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/project/)
-#[derive(PartialEq, PartialOrd, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Environment {
     /// Unique ID.
     pub id: String,
     /// Name.
-    pub name: String
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kind of resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// State.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Health of the environment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_state: Option<HealthState>,
+    /// Orchestration engine: `cattle`, `kubernetes`, `mesos`, `swarm`, `windows`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orchestration: Option<String>,
+    /// ID of the template used to create the environment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_template_id: Option<String>,
+    /// ID of the default network.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_network_id: Option<String>,
+    /// Members of the environment.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub members: Vec<serde_json::Value>,
+    /// ID of the account owning the resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Creation date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Removal date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<String>,
+    /// Universally unique ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Whether an asynchronous operation is running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning: Option<Transitioning>,
+    /// Message of the running operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_message: Option<String>,
+    /// Progress of the running operation, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_progress: Option<u64>,
+    /// Links to the resource itself and its related resources.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub links: HashMap<String, String>,
+    /// Actions currently allowed on the resource, with their URL.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub actions: HashMap<String, String>,
+    /// Every other field returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
 }
 
 
-impl Environment {
-    /// Get all environments.
-    pub fn get_all(rancher: &mut Rancher) -> Result<Vec<Environment>, &'static str> {
-        let mut data: Vec<Environment> = Vec::new();
-        let results: serde_json::Value = rancher.call_api("/projects").unwrap();
-        let projects = results["data"].as_array().unwrap();
-        for project in projects {
-            data.push(Environment{
-                id: project["id"].as_str().unwrap().to_string(),
-                name: project["name"].as_str().unwrap().to_string()
-            });
-        }
-        return Ok(data);
-    }
-    /// Get one environment from a pattern that should match with the environment id or the
-    /// environment name.
-    pub fn get_one(rancher: &mut Rancher, pattern: &str) -> Result<Environment, &'static str> {
-        let results: serde_json::Value = rancher.call_api("/projects").unwrap();
-        let projects = results["data"].as_array().unwrap();
-        for project in projects {
-            if pattern == project["id"].as_str().unwrap() || pattern == project["name"].as_str().unwrap() {
-                return Ok(Environment{
-                    id: project["id"].as_str().unwrap().to_string(),
-                    name: project["name"].as_str().unwrap().to_string()
-                });
-            }
-        }
-        return Err("No environment found.");
-    }
-}
-
+impl Resource for Environment {
+    type Parent = ();
+    type Id = str;
 
-/// This trait allows you to call Environment's functions with `rancher.get_environment(........)`.
-pub trait EnvironmentTrait {
-    /// Get all environments.
-    fn get_environments(&mut self) -> Result<Vec<Environment>, &'static str>;
-    /// Get one environment from a pattern that should match with the environment id or the
-    /// environment name.
-    fn get_environment(&mut self, pattern: &str) -> Result<Environment, &'static str>;
-}
+    const TYPE: &'static str = "project";
 
-impl EnvironmentTrait for Rancher {
-    fn get_environments(&mut self) -> Result<Vec<Environment>, &'static str> {
-        return Environment::get_all(self);
-    }
-    fn get_environment(&mut self, pattern: &str) -> Result<Environment, &'static str> {
-        return Environment::get_one(self, pattern);
+    fn collection_path(_: &()) -> String {
+        return String::from("/projects");
     }
 }
 
@@ -66,19 +86,13 @@ impl EnvironmentTrait for Rancher {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rancher::Rancher;
+    use error::Error;
+    use query::Query;
+    use transport::{Method, MockTransport};
+    use test_util;
 
-    #[test]
-    fn get_all() {
-        let expected_result = vec![
-            Environment{
-                id: "1a10".to_owned(),
-                name: "fakeEnvironment1".to_owned(),
-            },
-            Environment{
-                id: "1a11".to_owned(),
-                name: "fakeEnvironment2".to_owned(),
-            }
-        ];
+    fn rancher() -> Rancher {
         let api_response = r#"{
                                 "data": [
                                     {
@@ -91,53 +105,71 @@ mod tests {
                                     }
                                 ]
                             }"#;
-        let mut data: Vec<Environment> = Vec::new();
-        let results: serde_json::Value = serde_json::from_str(api_response).unwrap();
-        let projects = results["data"].as_array().unwrap();
-        for project in projects {
-            data.push(Environment{
-                id: project["id"].as_str().unwrap().to_string(),
-                name: project["name"].as_str().unwrap().to_string()
-            });
-        }
+        let transport = MockTransport::new()
+            .on(Method::Get, "/projects", 200, api_response)
+            .on(Method::Get, "/projects?name=fakeEnvironment2&limit=1", 200, r#"{
+                "data": [
+                    {
+                        "id": "1a11",
+                        "name": "fakeEnvironment2"
+                    }
+                ]
+            }"#)
+            .on(Method::Get, "/projects?name=missing&limit=1", 200, r#"{"data": []}"#)
+            .on(Method::Get, "/projects/1a11", 200, r#"{"id": "1a11", "name": "fakeEnvironment2"}"#);
+        return test_util::rancher(transport);
+    }
 
-        assert_eq!(expected_result.eq(&data), true, "Expected {:?}, got {:?}", expected_result, data);
+    #[test]
+    fn get_all() {
+        let expected_result = vec![
+            Environment{
+                id: "1a10".to_owned(),
+                name: Some("fakeEnvironment1".to_owned()),
+                ..Environment::default()
+            },
+            Environment{
+                id: "1a11".to_owned(),
+                name: Some("fakeEnvironment2".to_owned()),
+                ..Environment::default()
+            }
+        ];
+        let data = rancher().list::<Environment>(&(), &Query::new()).unwrap();
+
+        assert!(expected_result.eq(&data), "Expected {:?}, got {:?}", expected_result, data);
     }
-    
+
     #[test]
-    fn get_one() {
+    fn find_by_name() {
         let expected_result = Environment{
-            id: "1a10".to_owned(),
-            name: "fakeEnvironment1".to_owned(),
+            id: "1a11".to_owned(),
+            name: Some("fakeEnvironment2".to_owned()),
+            ..Environment::default()
         };
-        let api_response = r#"{
-                                "data": [
-                                    {
-                                        "id": "1a10",
-                                        "name": "fakeEnvironment1"
-                                    },
-                                    {
-                                        "id": "1a11",
-                                        "name": "fakeEnvironment2"
-                                    }
-                                ]
-                            }"#;
-        let pattern = "1a10";
-        let mut data: Option<Environment> = None;
-        let results: serde_json::Value = serde_json::from_str(api_response).unwrap();
-        let projects = results["data"].as_array().unwrap();
-        for project in projects {
-            if pattern == project["id"].as_str().unwrap() || pattern == project["name"].as_str().unwrap() {
-                data = Some(Environment{
-                    id: project["id"].as_str().unwrap().to_string(),
-                    name: project["name"].as_str().unwrap().to_string()
-                });
-                break;
-            }
+        let rancher = rancher();
+        let result = rancher.find_by_name::<Environment>(&(), "fakeEnvironment2").unwrap();
+        assert!(expected_result.eq(&result), "Expected {:?}, got {:?}", expected_result, result);
+
+        match rancher.find_by_name::<Environment>(&(), "missing") {
+            Err(Error::NotFound { resource_type, name, .. }) => {
+                assert_eq!((resource_type.as_deref(), name.as_deref()), (Some("project"), Some("missing")))
+            },
+            other => panic!("Expected NotFound, got {:?}", other)
         }
-        if data.is_some() {
-            let result = data.unwrap();
-            assert_eq!(expected_result.eq(&result), true, "Expected {:?}, got {:?}", expected_result, result);
+    }
+
+    #[test]
+    fn get() {
+        let rancher = rancher();
+        let result: Environment = rancher.get(&(), "1a11").unwrap();
+        assert_eq!(result.name, Some(String::from("fakeEnvironment2")));
+
+        match rancher.get::<Environment>(&(), "1a12") {
+            Err(Error::NotFound { resource_type, id, error, .. }) => {
+                assert_eq!((resource_type.as_deref(), id.as_deref()), (Some("project"), Some("1a12")));
+                assert_eq!(error.map(|e| e.status), Some(404));
+            },
+            other => panic!("Expected NotFound, got {:?}", other)
         }
     }
 }
@@ -0,0 +1,272 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+use curl;
+#[cfg(feature = "async")]
+use isahc;
+use serde_json;
+
+/// Maximum length of the detail kept from a body which is not a Rancher error.
+const MAX_DETAIL_LENGTH: usize = 200;
+
+/// Error body returned by the Rancher API when a request is rejected
+/// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#errors).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    /// HTTP status code reported by Rancher.
+    pub status: u32,
+    /// Machine readable error code, e.g. `NotFound` or `InvalidReference`.
+    pub code: String,
+    /// Human readable message.
+    pub message: Option<String>,
+    /// Additional details about the error.
+    pub detail: Option<String>,
+}
+
+impl ApiError {
+    /// Build an `ApiError` from a JSON body if its `type` or `baseType` is `error`.
+    pub fn from_value(value: &serde_json::Value) -> Option<ApiError> {
+        if value["baseType"] != "error" && value["type"] != "error" {
+            return None;
+        }
+        let status = match value["status"].as_u64() {
+            Some(v) => v as u32,
+            None => 0
+        };
+        return Some(ApiError {
+            status,
+            code: value["code"].as_str().unwrap_or("").to_string(),
+            message: value["message"].as_str().map(|v| v.to_string()),
+            detail: value["detail"].as_str().map(|v| v.to_string()),
+        });
+    }
+
+    /// Build an `ApiError` from a response whose body is not a Rancher error, e.g. the HTML page
+    /// of a reverse proxy, keeping the beginning of the body as detail.
+    pub(crate) fn from_status(status: u32, body: &str) -> ApiError {
+        let code = match status {
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "NotFound",
+            409 => "Conflict",
+            422 => "UnprocessableEntity",
+            _ => "ServerError",
+        };
+        let body = body.trim();
+        let detail = match body.char_indices().nth(MAX_DETAIL_LENGTH) {
+            Some((end, _)) => format!("{}...", &body[..end]),
+            None => body.to_string(),
+        };
+        return ApiError {
+            status,
+            code: code.to_string(),
+            message: None,
+            detail: if detail.is_empty() { None } else { Some(detail) },
+        };
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.status, self.code)?;
+        if let Some(ref message) = self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(ref detail) = self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Errors returned by every function of this crate.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response could not be received.
+    Transport(curl::Error),
+    /// The request could not be sent or the response could not be received by the asynchronous
+    /// client.
+    #[cfg(feature = "async")]
+    AsyncTransport(isahc::Error),
+    /// The server answered with an unsuccessful status code without a variant of its own, and a
+    /// body that is not a Rancher error.
+    Http {
+        /// HTTP status code.
+        status: u32,
+        /// Raw response body.
+        body: String
+    },
+    /// The response body is not valid UTF-8.
+    Utf8(FromUtf8Error),
+    /// The response body is not valid JSON.
+    Json(serde_json::Error),
+    /// The response is valid JSON but is not shaped as expected.
+    Decode(String),
+    /// The credentials are missing, invalid or expired (401).
+    Unauthorized(ApiError),
+    /// The credentials don't allow the request (403).
+    Forbidden(ApiError),
+    /// The request conflicts with the current state of the resource, e.g. a concurrent
+    /// update (409).
+    Conflict(ApiError),
+    /// Rancher rejected the content of the request, e.g. a missing or invalid field (422).
+    UnprocessableEntity(ApiError),
+    /// Rancher failed to process the request (5xx).
+    Server(ApiError),
+    /// Rancher rejected the request with another status and returned an error object.
+    Api(ApiError),
+    /// The resource or the path doesn't exist: the server answered with a 404, or nothing matches
+    /// the name given to `find_by_name`.
+    NotFound {
+        /// Type of the resource looked up by `get` or `find_by_name`, e.g. `container`.
+        resource_type: Option<String>,
+        /// ID given to `get`.
+        id: Option<String>,
+        /// Name given to `find_by_name`.
+        name: Option<String>,
+        /// Error object of the 404, if the server answered with one.
+        error: Option<Box<ApiError>>,
+    },
+    /// The action is not currently allowed on the resource.
+    UnavailableAction(String),
+    /// Rancher failed to process an asynchronous operation on a resource.
+    Transition(String),
+    /// A resource did not reach the expected state in time.
+    Timeout(String),
+    /// The request does not match the schema of the resource type, it was not sent.
+    Validation(String),
+    /// The URL of the Rancher API is invalid.
+    InvalidUrl(String),
+    /// The client configuration is missing or invalid.
+    Config(String),
+    /// A local file could not be read.
+    Io(io::Error),
+}
+
+impl Error {
+    /// Return the error of a request rejected by Rancher, with the variant matching its status.
+    pub fn from_api_error(error: ApiError) -> Error {
+        return match error.status {
+            401 => Error::Unauthorized(error),
+            403 => Error::Forbidden(error),
+            404 => Error::NotFound { resource_type: None, id: None, name: None, error: Some(Box::new(error)) },
+            409 => Error::Conflict(error),
+            422 => Error::UnprocessableEntity(error),
+            500..=599 => Error::Server(error),
+            _ => Error::Api(error),
+        };
+    }
+
+    /// Return the error object returned by Rancher, if the request was rejected.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        return match *self {
+            Error::Unauthorized(ref e) | Error::Forbidden(ref e) | Error::Conflict(ref e) |
+            Error::UnprocessableEntity(ref e) | Error::Server(ref e) | Error::Api(ref e) => Some(e),
+            Error::NotFound { ref error, .. } => error.as_deref(),
+            _ => None,
+        };
+    }
+
+    /// Return whether the request failed to reach the server or to get its response, e.g. the
+    /// connection was refused or timed out, so that sending it again may succeed.
+    pub fn is_connection_error(&self) -> bool {
+        return match *self {
+            Error::Transport(ref e) => e.is_couldnt_connect() || e.is_couldnt_resolve_host() ||
+                e.is_operation_timedout() || e.is_recv_error() || e.is_send_error(),
+            #[cfg(feature = "async")]
+            Error::AsyncTransport(ref e) => matches!(*e.kind(),
+                isahc::error::ErrorKind::ConnectionFailed | isahc::error::ErrorKind::NameResolution |
+                isahc::error::ErrorKind::Timeout | isahc::error::ErrorKind::Io),
+            _ => false,
+        };
+    }
+
+    /// Return the HTTP status code of a rejected request.
+    pub fn status(&self) -> Option<u32> {
+        return match *self {
+            Error::Http { status, .. } => Some(status),
+            _ => self.api_error().map(|e| e.status),
+        };
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Transport(ref e) => write!(f, "transport error: {}", e),
+            #[cfg(feature = "async")]
+            Error::AsyncTransport(ref e) => write!(f, "transport error: {}", e),
+            Error::Http { status, ref body } => write!(f, "HTTP error {}: {}", status, body),
+            Error::Utf8(ref e) => write!(f, "invalid UTF-8 in response: {}", e),
+            Error::Json(ref e) => write!(f, "invalid JSON in response: {}", e),
+            Error::Decode(ref msg) => write!(f, "unexpected response: {}", msg),
+            Error::Unauthorized(ref e) => write!(f, "unauthorized: {}", e),
+            Error::Forbidden(ref e) => write!(f, "forbidden: {}", e),
+            Error::Conflict(ref e) => write!(f, "conflict: {}", e),
+            Error::UnprocessableEntity(ref e) => write!(f, "invalid request content: {}", e),
+            Error::Server(ref e) => write!(f, "server error: {}", e),
+            Error::Api(ref e) => write!(f, "Rancher API error: {}", e),
+            Error::NotFound { ref resource_type, ref id, ref name, ref error } => {
+                let resource_type = resource_type.as_deref().unwrap_or("resource");
+                match (id.as_ref(), name.as_ref(), error.as_deref()) {
+                    (Some(id), _, _) => write!(f, "not found: no {} with ID `{}`", resource_type, id),
+                    (_, Some(name), _) => write!(f, "not found: no {} named `{}`", resource_type, name),
+                    (_, _, Some(e)) => write!(f, "not found: {}", e),
+                    _ => write!(f, "not found: {}", resource_type),
+                }
+            },
+            Error::UnavailableAction(ref msg) => write!(f, "action not available: {}", msg),
+            Error::Transition(ref msg) => write!(f, "transition failed: {}", msg),
+            Error::Timeout(ref msg) => write!(f, "timed out: {}", msg),
+            Error::Validation(ref msg) => write!(f, "invalid request: {}", msg),
+            Error::InvalidUrl(ref msg) => write!(f, "invalid URL: {}", msg),
+            Error::Config(ref msg) => write!(f, "configuration error: {}", msg),
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Transport(ref e) => Some(e),
+            #[cfg(feature = "async")]
+            Error::AsyncTransport(ref e) => Some(e),
+            Error::Utf8(ref e) => Some(e),
+            Error::Json(ref e) => Some(e),
+            Error::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<curl::Error> for Error {
+    fn from(e: curl::Error) -> Error {
+        return Error::Transport(e);
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(e: FromUtf8Error) -> Error {
+        return Error::Utf8(e);
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        return Error::Json(e);
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        return Error::Io(e);
+    }
+}
+
+/// Return the `data` array of a Rancher collection.
+pub(crate) fn collection_data(results: &serde_json::Value) -> Result<&Vec<serde_json::Value>, Error> {
+    return results["data"].as_array()
+        .ok_or_else(|| Error::Decode(String::from("collection without `data` array")));
+}
@@ -1,123 +1,125 @@
-use serde_json;
+use serde_json::{self, Map};
 use super::Rancher;
 use std::collections::HashMap;
+use error::Error;
+use model;
+use state::{HostState, AgentState, Transitioning};
+use resource::Resource;
 
 /// Struct that contains all data for a Rancher Host
 /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/host/).
-#[derive(PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Host {
-    /// Host unique ID.
+    /// Unique ID.
     pub id: String,
+    /// Name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kind of resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
     /// Hostname.
-    pub hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// State.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<HostState>,
     /// IP address of the host.
-    pub agent_ip_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_ip_address: Option<String>,
     /// State of the agent.
-    pub agent_state: String,
-    /// Labels associated with the host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_state: Option<AgentState>,
+    /// ID of the agent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Labels.
+    #[serde(default, deserialize_with = "model::null_as_default")]
     pub labels: HashMap<String, String>,
+    /// Driver used to provision the host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+    /// Memory of the host, in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+    /// Local storage of the host, in megabytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_storage_mb: Option<u64>,
+    /// CPU reserved on the host, in millicores.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milli_cpu_reservation: Option<u64>,
+    /// ID of the physical host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub physical_host_id: Option<String>,
+    /// Ports published on the host.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub public_endpoints: Vec<serde_json::Value>,
+    /// Hardware and OS information reported by the agent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<serde_json::Value>,
+    /// ID of the account owning the resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Creation date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Removal date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<String>,
+    /// Universally unique ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Whether an asynchronous operation is running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning: Option<Transitioning>,
+    /// Message of the running operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_message: Option<String>,
+    /// Progress of the running operation, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_progress: Option<u64>,
+    /// Links to the resource itself and its related resources.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub links: HashMap<String, String>,
+    /// Actions currently allowed on the resource, with their URL.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub actions: HashMap<String, String>,
+    /// Every other field returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
 }
 
 impl Host {
-    /// Get all hosts from an environment.
-    pub fn get_all(rancher: &mut Rancher, environment_id: &str) -> Result<Vec<Host>, &'static str> {
-        let mut data: Vec<Host> = Vec::new();
-        let path = format!("/projects/{}/hosts", environment_id);
-        let results = rancher.call_api(&path).unwrap();
-        let hosts = results["data"].as_array().unwrap();
-        for host in hosts {
-            let mut labels: HashMap<String, String> = HashMap::new();
-            if host["labels"].as_object().is_some() {
-                for (label, value) in host["labels"].as_object().unwrap() {
-                    labels.insert(label.to_string(), value.as_str().unwrap_or("").to_string().replace("\"", ""));
-                }
-            }
-
-            data.push(Host{
-                id: host["id"].as_str().unwrap().to_string(),
-                hostname: host["hostname"].as_str().unwrap().to_string(),
-                agent_ip_address: host["agentIpAddress"].as_str().unwrap().to_string(),
-                agent_state: host["agentState"].as_str().unwrap_or("").to_string(),
-                labels: labels,
-            });
-        }
-        return Ok(data);
-    }
-    /// Get an host from an environment and a pattern that should match with the host ID or the
-    /// hostname.
-    pub fn get_one(rancher: &mut Rancher, environment_id: &str, pattern: &str) -> Result<Host, &'static str> {
-        let path = format!("/projects/{}/hosts", environment_id);
-        let results: serde_json::Value = rancher.call_api(&path).unwrap();
-        let hosts = results["data"].as_array().unwrap();
-        for host in hosts {
-            let mut labels: HashMap<String, String> = HashMap::new();
-            if host["labels"].as_object().is_some() {
-                for (label, value) in host["labels"].as_object().unwrap() {
-                    labels.insert(label.to_string(), value.as_str().unwrap_or("").to_string());
-                }
-            }
-
-            if pattern == host["id"].as_str().unwrap() || pattern == host["hostname"].as_str().unwrap() {
-                return Ok(Host{
-                    id: host["id"].as_str().unwrap().to_string(),
-                    hostname: host["hostname"].as_str().unwrap().to_string(),
-                    agent_ip_address: host["agentIpAddress"].as_str().unwrap().to_string(),
-                    agent_state: host["agentState"].as_str().unwrap_or("").to_string(),
-                    labels: labels
-                });
-            }
-        }
-        return Err("No host found.");
+    /// Replace the labels of an host from an environment.
+    pub fn set_labels(rancher: &Rancher, environment_id: &str, host_id: &str, labels: &HashMap<String, String>) -> Result<Host, Error> {
+        return rancher.update::<Host>(environment_id, host_id, &json!({ "labels": labels }));
     }
 }
 
-/// This trait allows you to call Host's functions with `rancher.get_host(.......)`.
-pub trait HostTrait {
-    /// Get all hosts from an environment.
-    fn get_hosts(&mut self, environment_id: &str) -> Result<Vec<Host>, &'static str>;
-    /// Get an host from an environment and a pattern that should match with the host ID or the
-    /// hostname.
-    fn get_host(&mut self, environment_id: &str, pattern: &str) -> Result<Host, &'static str>;
-}
+impl Resource for Host {
+    type Parent = str;
+    type Id = str;
 
-impl HostTrait for Rancher {
-    fn get_hosts(&mut self, environment_id: &str) -> Result<Vec<Host>, &'static str> {
-        return Host::get_all(self, environment_id);
-    }
+    const TYPE: &'static str = "host";
+    const NAME_FIELD: &'static str = "hostname";
 
-    fn get_host(&mut self, environment_id: &str, pattern: &str) -> Result<Host, &'static str> {
-        return Host::get_one(self, environment_id, pattern);
+    fn collection_path(environment_id: &str) -> String {
+        return format!("/projects/{}/hosts", environment_id);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use query::Query;
+    use transport::{Method, MockTransport};
+    use test_util;
 
-    #[test]
-    fn get_all() {
-        let expected_result = vec![
-            Host{
-                id: String::from("1h10"),
-                hostname: String::from("fake-10-10-10-10.internal"),
-                agent_ip_address: String::from("10.10.10.10"),
-                agent_state: String::from("active"),
-                labels: [
-                    (String::from("database"), String::from("true")),
-                    (String::from("monitoring"), String::from("false")),
-                ].iter().cloned().collect(),
-            },
-            Host{
-                id: String::from("1h11"),
-                hostname: String::from("fake-10-10-10-11.internal"),
-                agent_ip_address: String::from("10.10.10.11"),
-                agent_state: String::from("disconnected"),
-                labels: [
-                    (String::from("database"), String::from("false")),
-                ].iter().cloned().collect(),
-            }
-
-        ];
+    fn rancher() -> Rancher {
         let api_response = r#"{
             "data": [
                 {
@@ -141,89 +143,121 @@ mod tests {
                 }
             ]
         }"#;
-        let mut data: Vec<Host> = Vec::new();
-        let results: serde_json::Value = serde_json::from_str(api_response).unwrap();
-        let hosts = results["data"].as_array().unwrap();
-        for host in hosts {
-            let mut labels: HashMap<String, String> = HashMap::new();
-            if host["labels"].as_object().is_some() {
-                for (label, value) in host["labels"].as_object().unwrap() {
-                    labels.insert(label.to_string(), value.as_str().unwrap_or("").to_string().replace("\"", ""));
-                }
+        let transport = MockTransport::new()
+            .on(Method::Get, "/projects/1a5/hosts", 200, api_response)
+            .on(Method::Get, "/projects/1a5/hosts?agentState=disconnected", 200, r#"{
+                "data": [
+                    {
+                        "id": "1h11",
+                        "hostname": "fake-10-10-10-11.internal",
+                        "agentIpAddress": "10.10.10.11",
+                        "agentState": "disconnected",
+                        "labels": {
+                            "database": "false"
+                        }
+                    }
+                ]
+            }"#)
+            .on(Method::Get, "/projects/1a5/hosts?hostname=fake-10-10-10-11.internal&limit=1", 200, r#"{
+                "data": [
+                    {
+                        "id": "1h11",
+                        "hostname": "fake-10-10-10-11.internal",
+                        "agentIpAddress": "10.10.10.11",
+                        "agentState": "disconnected",
+                        "labels": {
+                            "database": "false"
+                        }
+                    }
+                ]
+            }"#);
+        return test_util::rancher(transport);
+    }
+
+    #[test]
+    fn get_all() {
+        let expected_result = vec![
+            Host{
+                id: String::from("1h10"),
+                hostname: Some(String::from("fake-10-10-10-10.internal")),
+                agent_ip_address: Some(String::from("10.10.10.10")),
+                agent_state: Some(AgentState::Active),
+                labels: [
+                    (String::from("database"), String::from("true")),
+                    (String::from("monitoring"), String::from("false")),
+                ].iter().cloned().collect(),
+                ..Host::default()
+            },
+            Host{
+                id: String::from("1h11"),
+                hostname: Some(String::from("fake-10-10-10-11.internal")),
+                agent_ip_address: Some(String::from("10.10.10.11")),
+                agent_state: Some(AgentState::Disconnected),
+                labels: [
+                    (String::from("database"), String::from("false")),
+                ].iter().cloned().collect(),
+                ..Host::default()
             }
 
-            data.push(Host{
-                id: host["id"].as_str().unwrap().to_string(),
-                hostname: host["hostname"].as_str().unwrap().to_string(),
-                agent_ip_address: host["agentIpAddress"].as_str().unwrap().to_string(),
-                agent_state: host["agentState"].as_str().unwrap_or("").to_string(),
-                labels: labels,
-            });
-        }
+        ];
+        let data = rancher().list::<Host>("1a5", &Query::new()).unwrap();
 
-        assert_eq!(expected_result.eq(&data), true, "Expected {:?}, got {:?}", expected_result, data);
+        assert!(expected_result.eq(&data), "Expected {:?}, got {:?}", expected_result, data);
     }
 
     #[test]
-    fn get_one() {
+    fn find_by_name() {
         let expected_result = Host{
             id: String::from("1h11"),
-            hostname: String::from("fake-10-10-10-11.internal"),
-            agent_ip_address: String::from("10.10.10.11"),
-            agent_state: String::from("disconnected"),
+            hostname: Some(String::from("fake-10-10-10-11.internal")),
+            agent_ip_address: Some(String::from("10.10.10.11")),
+            agent_state: Some(AgentState::Disconnected),
             labels: [
                 (String::from("database"), String::from("false")),
             ].iter().cloned().collect(),
+            ..Host::default()
         };
+        let result = rancher().find_by_name::<Host>("1a5", "fake-10-10-10-11.internal").unwrap();
+        assert!(expected_result.eq(&result), "Expected {:?}, got {:?}", expected_result, result);
+
+        let data = rancher().list::<Host>("1a5", &Query::new().eq("agentState", "disconnected")).unwrap();
+        assert_eq!(data, vec![expected_result]);
+    }
+
+    #[test]
+    fn get_all_unknown_environment() {
+        match rancher().list::<Host>("1a6", &Query::new()) {
+            Err(Error::NotFound { error: Some(e), .. }) => assert_eq!(e.code, "NotFound"),
+            other => panic!("Expected NotFound, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn set_labels() {
+        use std::sync::Arc;
         let api_response = r#"{
-            "data": [
-                {
-                    "id": "1h10",
-                    "hostname": "fake-10-10-10-10.internal",
-                    "agentIpAddress": "10.10.10.10",
-                    "agentState": "active",
-                    "labels": {
-                        "database": "true",
-                        "monitoring": "false"
-                    }
-                },
-                {
-                    "id": "1h11",
-                    "hostname": "fake-10-10-10-11.internal",
-                    "agentIpAddress": "10.10.10.11",
-                    "agentState": "disconnected",
-                    "labels": {
-                        "database": "false"
-                    }
-                }
-            ]
-        }"#;
-        let pattern = "fake-10-10-10-11.internal";
-        let mut data: Option<Host> = None;
-        let results: serde_json::Value = serde_json::from_str(api_response).unwrap();
-        let hosts = results["data"].as_array().unwrap();
-        for host in hosts {
-            let mut labels: HashMap<String, String> = HashMap::new();
-            if host["labels"].as_object().is_some() {
-                for (label, value) in host["labels"].as_object().unwrap() {
-                    labels.insert(label.to_string(), value.as_str().unwrap_or("").to_string().replace("\"", ""));
-                }
+            "id": "1h10",
+            "hostname": "fake-10-10-10-10.internal",
+            "agentIpAddress": "10.10.10.10",
+            "agentState": "active",
+            "labels": {
+                "database": "false"
             }
+        }"#;
+        let transport = Arc::new(MockTransport::new()
+            .on(Method::Put, "/projects/1a5/hosts/1h10", 200, api_response)
+            .on(Method::Delete, "/projects/1a5/hosts/1h10", 204, ""));
+        let rancher = test_util::rancher(transport.clone());
+        let labels: HashMap<String, String> = [
+            (String::from("database"), String::from("false")),
+        ].iter().cloned().collect();
 
-            if pattern == host["id"].as_str().unwrap() || pattern == host["hostname"].as_str().unwrap() {
-                data = Some(Host{
-                    id: host["id"].as_str().unwrap().to_string(),
-                    hostname: host["hostname"].as_str().unwrap().to_string(),
-                    agent_ip_address: host["agentIpAddress"].as_str().unwrap().to_string(),
-                    agent_state: host["agentState"].as_str().unwrap_or("").to_string(),
-                    labels: labels,
-                });
-                break;
-            }
-        }
-        if data.is_some() {
-            let result = data.unwrap();
-            assert_eq!(expected_result.eq(&result), true, "Expected {:?}, got {:?}", expected_result, result);
-        }
+        let host = Host::set_labels(&rancher, "1a5", "1h10", &labels).unwrap();
+        assert_eq!(host.labels, labels);
+        rancher.delete::<Host>("1a5", "1h10").unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].body, Some(String::from(r#"{"labels":{"database":"false"}}"#)));
+        assert_eq!(requests[1].method, Method::Delete);
     }
 }
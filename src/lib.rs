@@ -6,15 +6,61 @@
         unsafe_code,
         unstable_features,
         unused_import_braces, unused_qualifications)]
+#![allow(clippy::needless_return)]
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
 extern crate serde_json;
 extern crate curl;
 extern crate regex;
 extern crate base64;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "async")]
+extern crate futures_timer;
+#[cfg(feature = "async")]
+extern crate isahc;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
 
 
 
+/// Use Error module.
+pub mod error;
+mod model;
+/// Use State module.
+pub mod state;
+/// Use Transport module.
+pub mod transport;
+/// Use Trace module.
+pub mod trace;
+/// Use ClientConfig module.
+pub mod client_config;
+/// Use Auth module.
+pub mod auth;
+/// Use Config module.
+pub mod config;
 /// Use Rancher module.
 pub mod rancher;
+/// Use Builder module.
+pub mod builder;
+/// Use AsyncRancher module.
+#[cfg(feature = "async")]
+pub mod async_rancher;
+/// Use Query module.
+pub mod query;
+/// Use Schema module.
+pub mod schema;
+/// Use Object module.
+pub mod object;
+/// Use Collection module.
+pub mod collection;
+/// Use Client module.
+pub mod client;
+/// Use Resource module.
+pub mod resource;
 /// Use Environment module.
 pub mod environment;
 /// Use Host module.
@@ -25,16 +71,27 @@ pub mod stack;
 pub mod service;
 /// Use Container module.
 pub mod container;
+#[cfg(test)]
+mod test_util;
 
 
+pub use error::Error;
 pub use rancher::Rancher;
-pub use environment::EnvironmentTrait;
+pub use builder::RancherBuilder;
+pub use auth::Auth;
+pub use config::{Config, Profile};
+pub use client_config::{ClientConfig, RetryPolicy, TlsConfig, ProxyConfig};
+#[cfg(feature = "async")]
+pub use async_rancher::AsyncRancher;
+pub use collection::Collection;
+pub use client::ResourceClient;
+pub use object::Object;
+pub use resource::{Resource, EnvironmentScope};
+pub use query::{Query, Order};
+pub use schema::{Schemas, Schema};
+pub use state::{ContainerState, AgentState, HostState, HealthState, ServiceState, AllocationState, Transitioning};
 pub use environment::Environment as Environment;
-pub use host::HostTrait;
 pub use host::Host as Host;
-pub use stack::StackTrait;
 pub use stack::Stack as Stack;
-pub use service::ServiceTrait;
 pub use service::Service as Service;
-pub use container::ContainerTrait;
 pub use container::Container as Container;
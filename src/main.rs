@@ -9,7 +9,7 @@ fn main(){
         .version("0.1")
         .author("Leo Depriester <leo.depriester@exadot.fr>")
         .arg(Arg::with_name("CONFIG_FILE")
-            .help("Name of the config file into ~/.rancher")
+            .help("Name of the profile in ~/.rancher/cli.json, or of a config file in ~/.rancher")
             .required(true)
             .index(1))
         .arg(Arg::with_name("COMMAND")
@@ -41,7 +41,7 @@ fn main(){
 
     let pattern = matches.value_of("CONFIG_FILE").unwrap();
 
-    let mut rancher = match Rancher::new_from_file(&pattern) {
+    let rancher = match Rancher::new_from_file(pattern) {
         Ok(v) => v,
         Err(e) => {
             println!("Error: {}", e);
@@ -49,11 +49,23 @@ fn main(){
         }
     };
 
-    let monitoring_env_id = rancher.get_environment("monitoring").unwrap().id;
-
+    let monitoring_env_id = match rancher.find_by_name::<Environment>(&(), "monitoring") {
+        Ok(v) => v.id,
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    for container in rancher.get_containers(&monitoring_env_id).unwrap() {
-        println!("{} -> {}", container.id, container.name);
+    let containers = match rancher.list::<Container>(&monitoring_env_id, &Query::new()) {
+        Ok(v) => v,
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    for container in containers {
+        println!("{} -> {}", container.id, container.name.unwrap_or_default());
     }
 
 }
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Deserializer};
+
+/// Deserialize a field the Rancher API may send as `null`, falling back to its default value.
+///
+/// Meant for collections such as `labels` or `serviceIds`, together with `#[serde(default)]` for
+/// missing fields.
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where D: Deserializer<'de>, T: Default + Deserialize<'de> {
+    return Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default());
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use state::Transitioning;
+use environment::Environment;
+use host::Host;
+use stack::Stack;
+use service::Service;
+use container::Container;
+
+/// Fields shared by every object returned by the Rancher API
+/// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#resources).
+pub trait Object {
+    /// Unique ID.
+    fn id(&self) -> &str;
+    /// Links to the resource itself and its related resources.
+    fn links(&self) -> &HashMap<String, String>;
+    /// Actions currently allowed on the resource, with their URL.
+    fn actions(&self) -> &HashMap<String, String>;
+    /// Whether an asynchronous operation is running.
+    fn transitioning(&self) -> Option<&Transitioning>;
+    /// Message of the running operation.
+    fn transitioning_message(&self) -> Option<&str>;
+
+    /// URL of the resource itself.
+    fn self_link(&self) -> Option<&str> {
+        return self.links().get("self").map(|v| v.as_str());
+    }
+}
+
+/// Implement `Object` for a model exposing the common fields.
+macro_rules! impl_object {
+    ($($name:ident),*) => {
+        $(
+            impl Object for $name {
+                fn id(&self) -> &str {
+                    return &self.id;
+                }
+                fn links(&self) -> &HashMap<String, String> {
+                    return &self.links;
+                }
+                fn actions(&self) -> &HashMap<String, String> {
+                    return &self.actions;
+                }
+                fn transitioning(&self) -> Option<&Transitioning> {
+                    return self.transitioning.as_ref();
+                }
+                fn transitioning_message(&self) -> Option<&str> {
+                    return self.transitioning_message.as_deref();
+                }
+            }
+        )*
+    };
+}
+
+impl_object!(Environment, Host, Stack, Service, Container);
@@ -0,0 +1,183 @@
+/// Sort order of a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Ascending order.
+    Asc,
+    /// Descending order.
+    Desc,
+}
+
+/// Server-side filters, sorting and limit of a Rancher collection
+/// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#filtering).
+///
+/// ```
+/// use rancher::{Query, Order};
+///
+/// let query = Query::new().eq("state", "running").prefix("name", "web").sort("created").order(Order::Desc);
+/// assert_eq!(query.to_query_string(), "?state=running&name_prefix=web&sort=created&order=desc");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    filters: Vec<(String, String)>,
+    sort: Option<String>,
+    order: Option<Order>,
+    limit: Option<u32>,
+}
+
+impl Query {
+    /// Return a query without any filter.
+    pub fn new() -> Query {
+        return Query::default();
+    }
+
+    /// Add a raw `name=value` parameter.
+    pub fn filter(mut self, name: &str, value: &str) -> Query {
+        self.filters.push((name.to_string(), value.to_string()));
+        return self;
+    }
+
+    /// Keep items whose `field` is equal to `value`.
+    pub fn eq(self, field: &str, value: &str) -> Query {
+        return self.filter(field, value);
+    }
+
+    /// Keep items whose `field` is not equal to `value`.
+    pub fn ne(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_ne", field), value);
+    }
+
+    /// Keep items whose `field` starts with `value`.
+    pub fn prefix(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_prefix", field), value);
+    }
+
+    /// Keep items whose `field` matches the SQL `LIKE` pattern `value`.
+    pub fn like(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_like", field), value);
+    }
+
+    /// Keep items whose `field` does not match the SQL `LIKE` pattern `value`.
+    pub fn not_like(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_notlike", field), value);
+    }
+
+    /// Keep items whose `field` is lower than `value`.
+    pub fn lt(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_lt", field), value);
+    }
+
+    /// Keep items whose `field` is lower than or equal to `value`.
+    pub fn lte(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_lte", field), value);
+    }
+
+    /// Keep items whose `field` is greater than `value`.
+    pub fn gt(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_gt", field), value);
+    }
+
+    /// Keep items whose `field` is greater than or equal to `value`.
+    pub fn gte(self, field: &str, value: &str) -> Query {
+        return self.filter(&format!("{}_gte", field), value);
+    }
+
+    /// Keep items whose `field` is null.
+    pub fn null(self, field: &str) -> Query {
+        return self.filter(&format!("{}_null", field), "");
+    }
+
+    /// Keep items whose `field` is not null.
+    pub fn not_null(self, field: &str) -> Query {
+        return self.filter(&format!("{}_notnull", field), "");
+    }
+
+    /// Sort items by `field`.
+    pub fn sort(mut self, field: &str) -> Query {
+        self.sort = Some(field.to_string());
+        return self;
+    }
+
+    /// Set the sort order.
+    pub fn order(mut self, order: Order) -> Query {
+        self.order = Some(order);
+        return self;
+    }
+
+    /// Set the maximum number of items returned per page.
+    pub fn limit(mut self, limit: u32) -> Query {
+        self.limit = Some(limit);
+        return self;
+    }
+
+    /// Return the parameters as `(name, value)` pairs, in the order they are sent.
+    pub fn params(&self) -> Vec<(String, String)> {
+        let mut params = self.filters.clone();
+        if let Some(ref sort) = self.sort {
+            params.push((String::from("sort"), sort.clone()));
+        }
+        match self.order {
+            Some(Order::Asc) => params.push((String::from("order"), String::from("asc"))),
+            Some(Order::Desc) => params.push((String::from("order"), String::from("desc"))),
+            None => {}
+        }
+        if let Some(limit) = self.limit {
+            params.push((String::from("limit"), limit.to_string()));
+        }
+        return params;
+    }
+
+    /// Return the query string, starting with `?`, or an empty string if the query is empty.
+    pub fn to_query_string(&self) -> String {
+        let params: Vec<String> = self.params().iter()
+            .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+            .collect();
+        if params.is_empty() {
+            return String::new();
+        }
+        return format!("?{}", params.join("&"));
+    }
+
+    /// Append the query to `path`, which may already contain a query string.
+    pub fn append_to(&self, path: &str) -> String {
+        let query = self.to_query_string();
+        if query.is_empty() {
+            return path.to_string();
+        }
+        if path.contains('?') {
+            return format!("{}&{}", path, &query[1..]);
+        }
+        return format!("{}{}", path, query);
+    }
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986.
+pub(crate) fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    return encoded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_query_string() {
+        assert_eq!(Query::new().to_query_string(), "");
+        let query = Query::new().ne("state", "removed").like("name", "web%").null("removed").limit(10);
+        assert_eq!(query.to_query_string(), "?state_ne=removed&name_like=web%25&removed_null=&limit=10");
+    }
+
+    #[test]
+    fn append_to() {
+        let query = Query::new().eq("name", "my stack");
+        assert_eq!(query.append_to("/projects/1a5/stacks"), "/projects/1a5/stacks?name=my%20stack");
+        assert_eq!(query.append_to("/projects/1a5/stacks?limit=5"), "/projects/1a5/stacks?limit=5&name=my%20stack");
+        assert_eq!(Query::new().append_to("/projects"), "/projects");
+    }
+}
@@ -1,156 +1,447 @@
 use *;
-use curl::easy::{Easy, List};
 use std;
-use std::fs;
-use std::io::prelude::*;
-use regex::Regex;
+use std::thread;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+use serde::de::DeserializeOwned;
+use error::{Error, ApiError};
+use transport::{Transport, CurlTransport, Method, Request, Response};
+use object::Object;
+use state::Transitioning;
+use schema::{Schemas, Schema};
+use client_config::{self, ClientConfig, RetryEvent};
+use builder::RancherBuilder;
+use auth::{self, Auth, Session};
+use trace::{self, ResponseEvent};
+use config::Config;
+use resource::EnvironmentScope;
+use query;
+use collection::Collection;
+use client::ResourceClient;
 
 /// Struct that contains basic informations needed to use the Rancher API.
+///
+/// Every method takes `&self`: a `Rancher` can be shared across threads, and cloning it is cheap
+/// since clones share the same transport and schema cache.
+#[derive(Clone)]
 pub struct Rancher {
+    inner: Arc<Inner>
+}
+
+/// State shared by every clone of a `Rancher`.
+struct Inner {
     /// URL of the Rancher API.
     url: String,
-    /// Access key of the Rancher API.
-    access_key: String,
-    /// Secret key of the Rancher API.
-    secret_key: String
+    /// Credentials, and session token once logged in.
+    session: Session,
+    /// Transport used to send requests.
+    transport: Box<dyn Transport>,
+    /// Timeouts and retry policy.
+    config: ClientConfig,
+    /// Schemas fetched from the server, on first use.
+    schemas: RwLock<Option<Arc<Schemas>>>,
+    /// Default environment, by ID or name.
+    environment: Option<String>,
+    /// ID of the default environment, once resolved.
+    environment_id: RwLock<Option<String>>
 }
 
 impl Rancher {
     /// Return a Rancher struct filled with informations provided as arguments.
     pub fn new(url: String, access_key: String, secret_key: String) -> Rancher {
+        return Rancher::with_config(url, access_key, secret_key, ClientConfig::new());
+    }
+    /// Return a Rancher struct applying the timeouts and retry policy of `config`.
+    pub fn with_config(url: String, access_key: String, secret_key: String, config: ClientConfig) -> Rancher {
+        let transport = CurlTransport::with_config(&config);
+        return Rancher::with_transport_and_config(url, access_key, secret_key, transport, config);
+    }
+    /// Return a Rancher struct sending its requests through `transport` instead of curl.
+    pub fn with_transport<T>(url: String, access_key: String, secret_key: String, transport: T) -> Rancher
+        where T: Transport + 'static {
+        return Rancher::with_transport_and_config(url, access_key, secret_key, transport, ClientConfig::new());
+    }
+    /// Return a Rancher struct sending its requests through `transport` and retrying them
+    /// according to `config`. Timeouts are up to the transport.
+    pub fn with_transport_and_config<T>(url: String, access_key: String, secret_key: String, transport: T, config: ClientConfig) -> Rancher
+        where T: Transport + 'static {
+        let auth = Auth::Basic { access_key, secret_key };
+        return Rancher::with_parts(url, auth, Box::new(transport), config, None);
+    }
+    /// Return a Rancher struct from every setting, see `RancherBuilder`.
+    pub(crate) fn with_parts(url: String, auth: Auth, transport: Box<dyn Transport>, config: ClientConfig,
+                             environment: Option<String>) -> Rancher {
         let rancher: Rancher = Rancher {
-            url: url,
-            access_key: access_key,
-            secret_key: secret_key
+            inner: Arc::new(Inner {
+                url,
+                session: Session::new(auth),
+                transport,
+                config,
+                schemas: RwLock::new(None),
+                environment,
+                environment_id: RwLock::new(None)
+            })
         };
         return rancher;
     }
-    /// Return a Rancher struct filled with informations stored into the file provided as argument.
-    pub fn new_from_file(pattern: &str) -> Result<Rancher, &'static str> {
-        let mut rancher_dir = std::env::home_dir().unwrap().to_str().unwrap().to_string();
-        rancher_dir.push_str("/.rancher/");
-        let mut files: Vec<String> = Vec::new();
-        let mut f: String;
-        for path in fs::read_dir(rancher_dir).unwrap() {
-            f = path.unwrap().path().to_str().unwrap().to_string();
-            if f.contains(&*pattern) {
-                files.push(f);
+    /// Return a builder to set the URL, credentials and client settings one by one.
+    pub fn builder() -> RancherBuilder {
+        return RancherBuilder::new();
+    }
+    /// Return a Rancher struct filled with the profile `name` of the configuration file, see
+    /// `Config`, or else with the file `~/.rancher/<name>` or `~/.rancher/<name>.json`.
+    ///
+    /// `Error::Config` is returned if `RANCHER_CONFIG` names a file which can't be read.
+    pub fn new_from_file(name: &str) -> Result<Rancher, Error> {
+        let path = Config::default_path()?;
+        if let Some(config) = Config::load_default()? {
+            if config.names().contains(&name) {
+                return Rancher::builder().profile(config.complete_profile(name)?)?.build();
+            }
+        }
+        let rancher_dir = match std::env::home_dir() {
+            Some(v) => v.join(".rancher"),
+            None => return Err(Error::Config(String::from("can't find the home directory")))
+        };
+        for file in &[rancher_dir.join(name), rancher_dir.join(format!("{}.json", name))] {
+            if file.is_file() {
+                let config = Config::load_from(file)?;
+                let profile = match config.default_name() {
+                    Some(v) => config.complete_profile(v)?,
+                    None => return Err(Error::Config(format!("no default profile in {}", file.display()))),
+                };
+                return Rancher::builder().profile(profile)?.build();
             }
         }
-        if files.len() != 1 {
-            return Err("Can't retrieve the config file.");
+        return Err(Error::Config(format!("no profile named `{}` in {}, nor file {}/{}[.json]",
+                                         name, path.display(), rancher_dir.display(), name)));
+    }
+    /// Return a Rancher struct filled with the `RANCHER_URL`, `RANCHER_ACCESS_KEY`,
+    /// `RANCHER_SECRET_KEY` and `RANCHER_ENVIRONMENT` environment variables, as used by the
+    /// official rancher CLI.
+    pub fn from_env() -> Result<Rancher, Error> {
+        return Rancher::builder().env().build();
+    }
+    /// Return a Rancher struct filled with the default profile of the configuration file, if it
+    /// exists, overridden by the environment variables read by `from_env`.
+    ///
+    /// `Error::Config` is returned if `RANCHER_CONFIG` names a file which can't be read.
+    pub fn from_default_sources() -> Result<Rancher, Error> {
+        let mut builder = Rancher::builder();
+        if let Some(config) = Config::load_default()? {
+            builder = builder.profile(config.default_profile()?)?;
         }
-        let mut file = fs::File::open(files[0].to_owned()).unwrap();
-        let mut content = String::new();
-        file.read_to_string(&mut content).unwrap();
-        let mut config: serde_json::Value = serde_json::from_str(&*content).unwrap();
-        // Set URL
-        if Regex::new(r"/schemas$").unwrap().is_match(config["url"].as_str().unwrap()) {
-            let url = config["url"].as_str().unwrap().replace("/schemas", "");
-            config["url"] = url.into();
+        return builder.env().build();
+    }
+    /// Return the ID of the default environment, resolving it on the first call: it's taken as
+    /// an ID if `/projects/<environment>` exists, and looked up by name otherwise.
+    ///
+    /// `Error::Config` is returned if no default environment is set.
+    pub fn environment_id(&self) -> Result<String, Error> {
+        if let Some(ref id) = *read(&self.inner.environment_id) {
+            return Ok(id.clone());
         }
-        let rancher: Rancher = Rancher {
-            url: config["url"].as_str().unwrap().to_string(),
-            access_key: config["accessKey"].as_str().unwrap().to_string(),
-            secret_key: config["secretKey"].as_str().unwrap().to_string()
+        let environment = match self.inner.environment {
+            Some(ref v) => v,
+            None => return Err(Error::Config(String::from("no default environment, set RANCHER_ENVIRONMENT"))),
         };
-        return Ok(rancher);
+        let id = match self.get::<Environment>(&(), &query::encode(environment)) {
+            Ok(v) => v.id,
+            Err(Error::NotFound { .. }) => self.find_by_name::<Environment>(&(), environment)?.id,
+            Err(e) => return Err(e),
+        };
+        *write(&self.inner.environment_id) = Some(id.clone());
+        return Ok(id);
     }
-    /// Send a GET request to the Rancher API.
-    pub fn call_api(&mut self, path: &str) -> Result<serde_json::Value, &'static str> {
-        let mut dst = String::new();
-        let mut handle = Easy::new();
-        let mut url = self.url.clone();
-        url.push_str(path);
-        handle.url(&url).unwrap();
-        let credentials = format!("{}:{}", self.access_key, self.secret_key);
-        let auth = format!("Authorization: Basic {}", base64::encode(&credentials));
-        let mut list = List::new();
-        list.append(&*auth).unwrap();
-        handle.http_headers(list).unwrap();
-        {
-            let mut transfer = handle.transfer();
-            transfer.write_function(|data| {
-                dst.push_str(std::str::from_utf8(data).unwrap());
-                Ok(data.len())
-            }).unwrap();
-            transfer.perform().unwrap();
+    /// Return a handle on the resources of the default environment.
+    pub fn default_environment(&self) -> Result<EnvironmentScope<'_>, Error> {
+        return Ok(EnvironmentScope::new(self, self.environment_id()?));
+    }
+    /// Return the schemas of every resource type, fetched from `/schemas` on the first call and
+    /// cached afterwards.
+    pub fn schemas(&self) -> Result<Arc<Schemas>, Error> {
+        if let Some(ref schemas) = *read(&self.inner.schemas) {
+            return Ok(schemas.clone());
         }
-        let results = serde_json::from_str(&*dst).unwrap();
-        return Ok(results);
+        return self.refresh_schemas();
+    }
+    /// Fetch `/schemas` again, e.g. after enabling a catalog or an orchestration engine.
+    pub fn refresh_schemas(&self) -> Result<Arc<Schemas>, Error> {
+        let list: Vec<Schema> = Collection::new(self, String::from("/schemas"), Schema::from_value)
+            .iter()
+            .collect::<Result<_, _>>()?;
+        let schemas = Arc::new(Schemas::new(list));
+        *write(&self.inner.schemas) = Some(schemas.clone());
+        return Ok(schemas);
+    }
+    /// Return a client for any resource type listed in `/schemas`, e.g. `volume`.
+    pub fn resource(&self, resource_type: &str) -> ResourceClient<'_> {
+        return ResourceClient::new(self, resource_type);
+    }
+    /// Send a GET request to the Rancher API.
+    pub fn call_api(&self, path: &str) -> Result<serde_json::Value, Error> {
+        return self.request(Method::Get, path, None);
     }
 
     /// Send a POST request to the Rancher API with data.
-    pub fn post_api(&mut self, path: &str, data: &str) -> Result<serde_json::Value, &'static str> {
-        let mut dst = String::new();
-        let mut handle = Easy::new();
-        let mut url = self.url.clone();
+    pub fn post_api(&self, path: &str, data: &str) -> Result<serde_json::Value, Error> {
+        return self.request(Method::Post, path, Some(data));
+    }
+    /// Send a POST request to the Rancher API without data.
+    pub fn post_api_without_data(&self, path: &str) -> Result<serde_json::Value, Error> {
+        return self.request(Method::Post, path, None);
+    }
+    /// Send a PUT request to the Rancher API with data.
+    pub fn put_api(&self, path: &str, data: &str) -> Result<serde_json::Value, Error> {
+        return self.request(Method::Put, path, Some(data));
+    }
+    /// Send a DELETE request to the Rancher API.
+    pub fn delete_api(&self, path: &str) -> Result<serde_json::Value, Error> {
+        return self.request(Method::Delete, path, None);
+    }
+
+    /// Send a request to the Rancher API and decode the JSON response.
+    ///
+    /// An empty response body, e.g. for a `204 No Content`, is returned as `Value::Null`.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method.
+    /// * `path` - A path relative to the Rancher API URL, e.g. `/projects`.
+    /// * `body` - An optional JSON body.
+    ///
+    pub fn request(&self, method: Method, path: &str, body: Option<&str>) -> Result<serde_json::Value, Error> {
+        let mut url = self.inner.url.clone();
         url.push_str(path);
-        handle.url(&url).unwrap();
-        handle.post(true).unwrap();
-        handle.post_field_size(data.len() as u64).unwrap();
-        let credentials = format!("{}:{}", self.access_key, self.secret_key);
-        let auth = format!("Authorization: Basic {}", base64::encode(&credentials));
-        let content_type = format!("Content-Type: application/json");
-        let mut list = List::new();
-        list.append(&*auth).unwrap();
-        list.append(&*content_type).unwrap();
-        handle.http_headers(list).unwrap();
-        {
-            let mut transfer = handle.transfer();
-            transfer.read_function(|buf| {
-                Ok(data.as_bytes().read(buf).unwrap_or(0))
-            }).unwrap();
-            transfer.perform().unwrap();
+        return self.request_url(method, &url, body);
+    }
+
+    /// Send a request to an absolute URL, e.g. a link returned by the Rancher API, and decode the
+    /// JSON response.
+    ///
+    /// Idempotent requests are retried according to the retry policy of the client, if any.
+    ///
+    /// With `Auth::Login`, the client logs in before the first request, and again once if the
+    /// session token is rejected.
+    pub fn request_url(&self, method: Method, url: &str, body: Option<&str>) -> Result<serde_json::Value, Error> {
+        if self.inner.session.needs_login() {
+            self.login()?;
         }
-        {
-            let mut transfer = handle.transfer();
-            transfer.write_function(|body| {
-                dst.push_str(std::str::from_utf8(body).unwrap());
-                Ok(body.len())
-            }).unwrap();
-            transfer.perform().unwrap();
+        return match self.send(method, url, body) {
+            Err(ref e) if self.inner.session.is_login() && auth::is_unauthorized(e) => {
+                self.login()?;
+                self.send(method, url, body)
+            },
+            result => result,
+        };
+    }
+
+    /// Log in with the username and password of `Auth::Login` and keep the new session token.
+    /// Nothing is done with other authentication modes.
+    pub fn login(&self) -> Result<(), Error> {
+        let request = match self.inner.session.login_request(&self.inner.url) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let response = decode_response(self.transmit(&request)?)?;
+        return self.inner.session.set_token(&response);
+    }
+
+    /// Send a request through the transport, logging it and calling the request and response
+    /// hooks.
+    fn transmit(&self, request: &Request) -> Result<Response, Error> {
+        let config = &self.inner.config;
+        if !trace::enabled() && config.on_request.is_none() && config.on_response.is_none() {
+            return self.inner.transport.send(request);
+        }
+        let redacted = trace::redact_request(request);
+        trace::log_request(&redacted);
+        if let Some(ref hook) = config.on_request {
+            hook(&redacted);
+        }
+        let start = Instant::now();
+        let result = self.inner.transport.send(request);
+        // Redacting means parsing the body again, only done if it's used.
+        let body = match result {
+            Ok(ref response) if trace::bodies_enabled() || config.on_response.is_some() => Some(trace::response_body(response)),
+            _ => None,
+        };
+        let event = ResponseEvent {
+            method: request.method,
+            url: &request.url,
+            status: result.as_ref().ok().map(|v| v.status),
+            duration: start.elapsed(),
+            body: body.as_deref(),
+            error: result.as_ref().err(),
+        };
+        trace::log_response(&event);
+        if let Some(ref hook) = config.on_response {
+            hook(&event);
         }
+        return result;
+    }
 
-        let results: serde_json::Value = serde_json::from_str(&*dst).unwrap();
-        /*
-        let base_type = results["baseType"].as_str().unwrap().to_owned();
-        if base_type == "error" {
-            return Err("Something goes wrong...");
-        } else {
-            return Ok(results);
-        }*/
-        return Ok(results);
+    /// Send a request, retrying it according to the retry policy, and decode the response.
+    fn send(&self, method: Method, url: &str, body: Option<&str>) -> Result<serde_json::Value, Error> {
+        let request = build_request(&self.inner.session, method, url, body);
+        let mut attempt: u32 = 0;
+        loop {
+            let result = self.transmit(&request);
+            let policy = match self.inner.config.retry {
+                Some(ref v) if v.allows(method, attempt) => v,
+                _ => return decode_response(result?),
+            };
+            let (status, delay) = match result {
+                Ok(ref response) if client_config::is_retryable_status(response.status) => {
+                    (Some(response.status), policy.delay(attempt, client_config::retry_after(response)))
+                },
+                Ok(response) => return decode_response(response),
+                Err(ref e) if e.is_connection_error() => (None, policy.delay(attempt, None)),
+                Err(e) => return Err(e),
+            };
+            attempt += 1;
+            if let Some(ref hook) = self.inner.config.on_retry {
+                hook(&RetryEvent { method, url, attempt, delay, status, error: result.as_ref().err() });
+            }
+            thread::sleep(delay);
+        }
     }
-    /// Send a POST request to the Rancher API without data.
-    pub fn post_api_without_data(&mut self, path: &str) -> Result<serde_json::Value, &'static str> {
-        let mut dst = String::new();
-        let mut handle = Easy::new();
-        let mut url = self.url.clone();
-        url.push_str(path);
-        handle.url(&url).unwrap();
-        handle.post(true).unwrap();
-        let credentials = format!("{}:{}", self.access_key, self.secret_key);
-        let auth = format!("Authorization: Basic {}", base64::encode(&credentials));
-        let mut list = List::new();
-        list.append(&*auth).unwrap();
-        handle.http_headers(list).unwrap();
-        {
-            let mut transfer = handle.transfer();
-            transfer.write_function(|body| {
-                dst.push_str(std::str::from_utf8(body).unwrap());
-                Ok(body.len())
-            }).unwrap();
-            transfer.perform().unwrap();
+
+    /// Invoke an action on a resource through the URL advertised in its `actions` map
+    /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#actions).
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource, as returned by a previous call.
+    /// * `action` - The action name, e.g. `evacuate` for a host.
+    /// * `input` - An optional JSON input for actions requiring one.
+    ///
+    /// `Error::UnavailableAction` is returned, without sending anything, if the action is not
+    /// currently allowed on the resource.
+    pub fn invoke<T: Object>(&self, resource: &T, action: &str, input: Option<&serde_json::Value>) -> Result<serde_json::Value, Error> {
+        let url = action_url(resource, action)?;
+        let body = input.map(|v| v.to_string());
+        return self.request_url(Method::Post, &url, body.as_deref());
+    }
+
+    /// Poll a resource through its `self` link until `predicate` returns true and return its last
+    /// version.
+    ///
+    /// Rancher processes actions asynchronously, so this is the way to know when e.g. a
+    /// container is actually running after a `start` action.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource to watch, as returned by a previous call.
+    /// * `predicate` - A function returning true once the resource reached the expected state.
+    /// * `timeout` - The maximum duration to wait, after which `Error::Timeout` is returned.
+    /// * `poll_interval` - The duration between two requests.
+    ///
+    /// `Error::Transition` is returned as soon as Rancher reports `transitioning: "error"`.
+    pub fn wait_until<T, F>(&self, resource: &T, predicate: F, timeout: Duration, poll_interval: Duration) -> Result<T, Error>
+        where T: Object + DeserializeOwned, F: Fn(&T) -> bool {
+        let url = match resource.self_link() {
+            Some(v) => v.to_string(),
+            None => return Err(Error::Decode(format!("resource {} without `self` link", resource.id())))
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            let current: T = serde_json::from_value(self.request_url(Method::Get, &url, None)?)?;
+            if current.transitioning() == Some(&Transitioning::Error) {
+                return Err(Error::Transition(format!("{}: {}", current.id(),
+                                                     current.transitioning_message().unwrap_or("unknown error"))));
+            }
+            if predicate(&current) {
+                return Ok(current);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout(format!("{} did not reach the expected state in {:?}", current.id(), timeout)));
+            }
+            thread::sleep(std::cmp::min(poll_interval, deadline - now));
         }
+    }
+}
+
+/// Build an authenticated request to `url`.
+pub(crate) fn build_request(session: &Session, method: Method, url: &str, body: Option<&str>) -> Request {
+    let mut headers: Vec<(String, String)> = session.header().into_iter().collect();
+    if body.is_some() {
+        headers.push((String::from("Content-Type"), String::from("application/json")));
+    }
+    return Request {
+        method,
+        url: url.to_string(),
+        headers,
+        body: body.map(|v| v.to_string()),
+    };
+}
 
-        let results: serde_json::Value = serde_json::from_str(&*dst).unwrap();
-        let base_type = results["baseType"].as_str().unwrap().to_owned();
-        if base_type == "error" {
-            return Err("Error: Something goes wrong...")
-        } else {
-            return Ok(results);
+/// Decode the JSON body of a response, turning unsuccessful status codes into errors.
+///
+/// Error objects returned by Rancher, whatever the status code, and unsuccessful responses are
+/// mapped to the variant of `Error` matching their status, see `Error::from_api_error`.
+pub(crate) fn decode_response(response: Response) -> Result<serde_json::Value, Error> {
+    let status = response.status;
+    let content = String::from_utf8(response.body)?;
+    if content.trim().is_empty() && status < 400 {
+        return Ok(serde_json::Value::Null);
+    }
+    let results: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(v) => v,
+        Err(e) => {
+            if status >= 400 {
+                return Err(http_error(status, content));
+            }
+            return Err(Error::Json(e));
         }
+    };
+    if let Some(mut e) = ApiError::from_value(&results) {
+        if e.status == 0 {
+            e.status = status;
+        }
+        return Err(Error::from_api_error(e));
+    }
+    if status >= 400 {
+        return Err(http_error(status, content));
     }
+    return Ok(results);
 }
 
+/// Return the error of an unsuccessful response whose body is not a Rancher error.
+fn http_error(status: u32, body: String) -> Error {
+    return match status {
+        401 | 403 | 404 | 409 | 422 | 500..=599 => Error::from_api_error(ApiError::from_status(status, &body)),
+        _ => Error::Http { status, body },
+    };
+}
+
+/// Return the URL of `action` on `resource`, or `Error::UnavailableAction` if the action is not
+/// currently allowed.
+pub(crate) fn action_url<T: Object>(resource: &T, action: &str) -> Result<String, Error> {
+    return match resource.actions().get(action) {
+        Some(v) => Ok(v.clone()),
+        None => {
+            let mut available: Vec<&str> = resource.actions().keys().map(|v| v.as_str()).collect();
+            available.sort();
+            Err(Error::UnavailableAction(format!("`{}` on {}, available actions: [{}]",
+                                                 action, resource.id(), available.join(", "))))
+        }
+    };
+}
+
+/// Lock `lock` for reading, ignoring poisoning since the cache is always left consistent.
+pub(crate) fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    return match lock.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+}
+
+/// Lock `lock` for writing, ignoring poisoning since the cache is always left consistent.
+pub(crate) fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    return match lock.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+}
@@ -0,0 +1,189 @@
+use serde_json;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::time::Duration;
+use super::Rancher;
+use error::Error;
+use object::Object;
+use collection::Collection;
+use query::Query;
+
+/// A resource type with a dedicated model in this crate.
+///
+/// Every operation shared by all resources is written once on `Rancher` on top of this trait:
+///
+/// ```no_run
+/// # use rancher::{Rancher, Query, Environment, Container};
+/// # let rancher = Rancher::new(String::from("http://rancher.test"), String::from("access"), String::from("secret"));
+/// let environment = rancher.find_by_name::<Environment>(&(), "monitoring").unwrap();
+/// for container in rancher.list::<Container>(&environment.id, &Query::new().eq("state", "running")).unwrap() {
+///     println!("{} -> {}", container.id, container.name.unwrap_or_default());
+/// }
+/// ```
+pub trait Resource: Object + DeserializeOwned {
+    /// What the collection belongs to: an environment ID, or `()` for environments themselves.
+    type Parent: ?Sized;
+    /// Type of the resource IDs.
+    type Id: ?Sized + fmt::Display;
+
+    /// Name of the type in `/schemas`, e.g. `container`.
+    const TYPE: &'static str;
+    /// Field matched by `find_by_name`.
+    const NAME_FIELD: &'static str = "name";
+
+    /// Return the path of the collection, relative to the Rancher API URL.
+    fn collection_path(parent: &Self::Parent) -> String;
+
+    /// Return the path of a resource, relative to the Rancher API URL.
+    fn resource_path(parent: &Self::Parent, id: &Self::Id) -> String {
+        return format!("{}/{}", Self::collection_path(parent), id);
+    }
+
+    /// Build the resource from an object returned by the Rancher API.
+    fn parse(value: &serde_json::Value) -> Result<Self, Error> {
+        return Ok(serde_json::from_value(value.clone())?);
+    }
+}
+
+impl Rancher {
+    /// Return a lazy handle on the resources of a collection.
+    pub fn collection<R: Resource>(&self, parent: &R::Parent) -> Collection<'_, R> {
+        return Collection::new(self, R::collection_path(parent), R::parse);
+    }
+
+    /// Get all resources of a collection matching `query`.
+    pub fn list<R: Resource>(&self, parent: &R::Parent, query: &Query) -> Result<Vec<R>, Error> {
+        return self.collection::<R>(parent).query(query).iter().collect();
+    }
+
+    /// Get a resource by ID.
+    ///
+    /// `Error::NotFound` is returned if Rancher answers with a 404.
+    pub fn get<R: Resource>(&self, parent: &R::Parent, id: &R::Id) -> Result<R, Error> {
+        let path = R::resource_path(parent, id);
+        return match self.call_api(&path) {
+            Ok(result) => R::parse(&result),
+            Err(e) => Err(not_found::<R>(e, &id)),
+        };
+    }
+
+    /// Get the first resource of a collection whose name, or hostname for hosts, is `name`.
+    pub fn find_by_name<R: Resource>(&self, parent: &R::Parent, name: &str) -> Result<R, Error> {
+        let query = Query::new().eq(R::NAME_FIELD, name);
+        return match self.collection::<R>(parent).query(&query).first()? {
+            Some(resource) => Ok(resource),
+            None => Err(name_not_found::<R>(name)),
+        };
+    }
+
+    /// Update a resource with the fields provided in `data`.
+    pub fn update<R: Resource>(&self, parent: &R::Parent, id: &R::Id, data: &serde_json::Value) -> Result<R, Error> {
+        let path = R::resource_path(parent, id);
+        let result = self.put_api(&path, &data.to_string())?;
+        return R::parse(&result);
+    }
+
+    /// Remove a resource.
+    pub fn delete<R: Resource>(&self, parent: &R::Parent, id: &R::Id) -> Result<(), Error> {
+        let path = R::resource_path(parent, id);
+        self.delete_api(&path)?;
+        return Ok(());
+    }
+
+    /// Get a resource by ID and poll it until `predicate` returns true, see `wait_until`.
+    pub fn watch<R, F>(&self, parent: &R::Parent, id: &R::Id, predicate: F, timeout: Duration, poll_interval: Duration) -> Result<R, Error>
+        where R: Resource, F: Fn(&R) -> bool {
+        let resource: R = self.get(parent, id)?;
+        if predicate(&resource) {
+            return Ok(resource);
+        }
+        return self.wait_until(&resource, predicate, timeout, poll_interval);
+    }
+}
+
+/// Resources of one environment, returned by `Rancher::default_environment`.
+///
+/// ```no_run
+/// # use rancher::{Rancher, Query, Container};
+/// // RANCHER_URL=... RANCHER_ACCESS_KEY=... RANCHER_SECRET_KEY=... RANCHER_ENVIRONMENT=monitoring
+/// let rancher = Rancher::from_env().unwrap();
+/// let containers = rancher.default_environment().unwrap().list::<Container>(&Query::new()).unwrap();
+/// ```
+pub struct EnvironmentScope<'a> {
+    rancher: &'a Rancher,
+    environment_id: String,
+}
+
+impl<'a> EnvironmentScope<'a> {
+    /// Return a handle on the resources of the environment `environment_id`.
+    pub(crate) fn new(rancher: &'a Rancher, environment_id: String) -> EnvironmentScope<'a> {
+        return EnvironmentScope {
+            rancher,
+            environment_id,
+        };
+    }
+
+    /// Return the ID of the environment.
+    pub fn id(&self) -> &str {
+        return &self.environment_id;
+    }
+
+    /// Return a lazy handle on the resources of a collection, see `Rancher::collection`.
+    pub fn collection<R: Resource<Parent = str>>(&self) -> Collection<'a, R> {
+        return self.rancher.collection::<R>(&self.environment_id);
+    }
+
+    /// Get all resources of a collection matching `query`, see `Rancher::list`.
+    pub fn list<R: Resource<Parent = str>>(&self, query: &Query) -> Result<Vec<R>, Error> {
+        return self.rancher.list::<R>(&self.environment_id, query);
+    }
+
+    /// Get a resource by ID, see `Rancher::get`.
+    pub fn get<R: Resource<Parent = str>>(&self, id: &R::Id) -> Result<R, Error> {
+        return self.rancher.get::<R>(&self.environment_id, id);
+    }
+
+    /// Get the first resource whose name is `name`, see `Rancher::find_by_name`.
+    pub fn find_by_name<R: Resource<Parent = str>>(&self, name: &str) -> Result<R, Error> {
+        return self.rancher.find_by_name::<R>(&self.environment_id, name);
+    }
+
+    /// Update a resource with the fields provided in `data`, see `Rancher::update`.
+    pub fn update<R: Resource<Parent = str>>(&self, id: &R::Id, data: &serde_json::Value) -> Result<R, Error> {
+        return self.rancher.update::<R>(&self.environment_id, id, data);
+    }
+
+    /// Remove a resource, see `Rancher::delete`.
+    pub fn delete<R: Resource<Parent = str>>(&self, id: &R::Id) -> Result<(), Error> {
+        return self.rancher.delete::<R>(&self.environment_id, id);
+    }
+}
+
+/// Add the type `R` and the ID of the resource to a 404, see `type_not_found`.
+pub(crate) fn not_found<R: Resource>(error: Error, id: &dyn fmt::Display) -> Error {
+    return type_not_found(error, R::TYPE, id);
+}
+
+/// Add the type and the ID of the resource to the `Error::NotFound` of a 404 returned while
+/// getting it.
+pub(crate) fn type_not_found(error: Error, resource_type: &str, id: &dyn fmt::Display) -> Error {
+    return match error {
+        Error::NotFound { error, .. } => Error::NotFound {
+            resource_type: Some(resource_type.to_string()),
+            id: Some(id.to_string()),
+            name: None,
+            error,
+        },
+        e => e,
+    };
+}
+
+/// Return the error of `find_by_name` when no resource is named `name`.
+pub(crate) fn name_not_found<R: Resource>(name: &str) -> Error {
+    return Error::NotFound {
+        resource_type: Some(R::TYPE.to_string()),
+        id: None,
+        name: Some(name.to_string()),
+        error: None,
+    };
+}
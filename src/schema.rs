@@ -0,0 +1,322 @@
+use serde_json::{self, Map};
+use std::collections::HashMap;
+use error::Error;
+use model;
+use query::Query;
+
+/// Modifiers a filter name may end with, e.g. `name_prefix`.
+const FILTER_MODIFIERS: [&str; 10] = ["ne", "prefix", "like", "notlike", "lt", "lte", "gt", "gte", "null", "notnull"];
+
+/// Query parameters which are not collection filters.
+const QUERY_PARAMETERS: [&str; 5] = ["sort", "order", "limit", "marker", "include"];
+
+/// Description of a resource field in a schema.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    /// Type of the field, e.g. `string`, `int`, `map[string]` or `reference[host]`.
+    #[serde(rename = "type")]
+    pub field_type: Option<String>,
+    /// Whether the field can be null.
+    pub nullable: Option<bool>,
+    /// Whether the field can be set on creation.
+    pub create: Option<bool>,
+    /// Whether the field can be set on update.
+    pub update: Option<bool>,
+    /// Whether the field is mandatory on creation.
+    pub required: Option<bool>,
+    /// Default value.
+    pub default: Option<serde_json::Value>,
+    /// Allowed values for `enum` fields.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub options: Vec<String>,
+    /// Every other attribute returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
+}
+
+/// Description of a collection filter in a schema.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+    /// Modifiers accepted by the filter, e.g. `eq`, `ne` or `prefix`.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub modifiers: Vec<String>,
+    /// Every other attribute returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
+}
+
+/// Description of an action in a schema.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Action {
+    /// Type of the input of the action, if it takes one.
+    pub input: Option<String>,
+    /// Type returned by the action.
+    pub output: Option<String>,
+    /// Every other attribute returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
+}
+
+/// Description of a resource type
+/// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#schemas).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    /// Name of the type, e.g. `container`.
+    pub id: String,
+    /// Name of the collection, e.g. `containers`.
+    pub plural_name: Option<String>,
+    /// Fields of a resource.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub resource_fields: HashMap<String, Field>,
+    /// Filters accepted by the collection.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub collection_filters: HashMap<String, Filter>,
+    /// Actions available on a resource.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub resource_actions: HashMap<String, Action>,
+    /// Actions available on the collection.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub collection_actions: HashMap<String, Action>,
+    /// HTTP methods accepted by a resource.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub resource_methods: Vec<String>,
+    /// HTTP methods accepted by the collection.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub collection_methods: Vec<String>,
+    /// Links to the schema itself and its collection.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub links: HashMap<String, String>,
+    /// Every other field returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
+}
+
+impl Schema {
+    /// Build a Schema from an object returned by the Rancher API.
+    pub(crate) fn from_value(schema: &serde_json::Value) -> Result<Schema, Error> {
+        return Ok(serde_json::from_value(schema.clone())?);
+    }
+
+    /// Check that a resource can be created with `body`: every field must exist and be
+    /// creatable, and every required field must be set.
+    pub fn validate_create(&self, body: &serde_json::Value) -> Result<(), Error> {
+        let object = self.object(body)?;
+        for (name, value) in object {
+            match self.resource_fields.get(name) {
+                Some(field) if field.create == Some(true) => {},
+                Some(_) => return Err(Error::Validation(format!("field `{}` of `{}` can't be set on creation", name, self.id))),
+                None => return Err(Error::Validation(format!("unknown field `{}` for `{}`", name, self.id))),
+            }
+            self.validate_value(name, value)?;
+        }
+        let mut required: Vec<&String> = self.resource_fields.iter()
+            .filter(|&(name, field)| field.required == Some(true) && field.default.is_none() && !object.contains_key(name))
+            .map(|(name, _)| name)
+            .collect();
+        required.sort();
+        if let Some(name) = required.first() {
+            return Err(Error::Validation(format!("missing required field `{}` for `{}`", name, self.id)));
+        }
+        return Ok(());
+    }
+
+    /// Check that a resource can be updated with `body`: every field must exist and be updatable.
+    pub fn validate_update(&self, body: &serde_json::Value) -> Result<(), Error> {
+        for (name, value) in self.object(body)? {
+            match self.resource_fields.get(name) {
+                Some(field) if field.update == Some(true) => {},
+                Some(_) => return Err(Error::Validation(format!("field `{}` of `{}` can't be updated", name, self.id))),
+                None => return Err(Error::Validation(format!("unknown field `{}` for `{}`", name, self.id))),
+            }
+            self.validate_value(name, value)?;
+        }
+        return Ok(());
+    }
+
+    /// Check that `action` exists on a resource and return its description.
+    pub fn validate_action(&self, action: &str) -> Result<&Action, Error> {
+        return self.resource_actions.get(action)
+            .ok_or_else(|| Error::Validation(format!("unknown action `{}` for `{}`", action, self.id)));
+    }
+
+    /// Check that every filter of `query` is accepted by the collection.
+    pub fn validate_query(&self, query: &Query) -> Result<(), Error> {
+        for (name, _) in query.params() {
+            if QUERY_PARAMETERS.contains(&name.as_str()) {
+                continue;
+            }
+            let (field, modifier) = split_filter(&name);
+            let filter = match self.collection_filters.get(field) {
+                Some(v) => v,
+                None => return Err(Error::Validation(format!("unknown filter `{}` for `{}`", field, self.id))),
+            };
+            if !filter.modifiers.is_empty() && !filter.modifiers.iter().any(|v| v == modifier) {
+                return Err(Error::Validation(format!("modifier `{}` not allowed on filter `{}` for `{}`", modifier, field, self.id)));
+            }
+        }
+        return Ok(());
+    }
+
+    /// Return `body` as a JSON object.
+    fn object<'a>(&self, body: &'a serde_json::Value) -> Result<&'a Map<String, serde_json::Value>, Error> {
+        return body.as_object()
+            .ok_or_else(|| Error::Validation(format!("body for `{}` must be a JSON object", self.id)));
+    }
+
+    /// Check that `value` is one of the allowed values of an `enum` field.
+    fn validate_value(&self, name: &str, value: &serde_json::Value) -> Result<(), Error> {
+        let field = match self.resource_fields.get(name) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        if value.is_null() && field.nullable == Some(false) {
+            return Err(Error::Validation(format!("field `{}` of `{}` can't be null", name, self.id)));
+        }
+        if let Some(v) = value.as_str() {
+            if !field.options.is_empty() && !field.options.iter().any(|o| o == v) {
+                return Err(Error::Validation(format!("`{}` is not a valid value for field `{}` of `{}`", v, name, self.id)));
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// Split a filter parameter into its field and modifier, e.g. `name_prefix` into `name` and
+/// `prefix`.
+fn split_filter(name: &str) -> (&str, &str) {
+    if let Some(pos) = name.rfind('_') {
+        let modifier = &name[pos + 1..];
+        if FILTER_MODIFIERS.contains(&modifier) {
+            return (&name[..pos], modifier);
+        }
+    }
+    return (name, "eq");
+}
+
+/// Every schema exposed by a Rancher server, indexed by type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schemas {
+    schemas: HashMap<String, Schema>,
+}
+
+impl Schemas {
+    /// Return the schemas of `list`, indexed by type.
+    pub fn new(list: Vec<Schema>) -> Schemas {
+        return Schemas {
+            schemas: list.into_iter().map(|schema| (schema.id.clone(), schema)).collect(),
+        };
+    }
+
+    /// Return the schema of a type, e.g. `container`.
+    pub fn get(&self, resource_type: &str) -> Option<&Schema> {
+        return self.schemas.get(resource_type);
+    }
+
+    /// Return the schema of a type or an `Error::Validation` if the type is unknown.
+    pub fn schema(&self, resource_type: &str) -> Result<&Schema, Error> {
+        return self.get(resource_type)
+            .ok_or_else(|| Error::Validation(format!("unknown type `{}`", resource_type)));
+    }
+
+    /// Return the names of every known type, sorted.
+    pub fn types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.schemas.keys().map(|v| v.as_str()).collect();
+        types.sort();
+        return types;
+    }
+
+    /// Check that a resource of `resource_type` can be created with `body`.
+    pub fn validate_create(&self, resource_type: &str, body: &serde_json::Value) -> Result<(), Error> {
+        return self.schema(resource_type)?.validate_create(body);
+    }
+
+    /// Check that a resource of `resource_type` can be updated with `body`.
+    pub fn validate_update(&self, resource_type: &str, body: &serde_json::Value) -> Result<(), Error> {
+        return self.schema(resource_type)?.validate_update(body);
+    }
+
+    /// Check that `action` exists on resources of `resource_type`.
+    pub fn validate_action(&self, resource_type: &str, action: &str) -> Result<&Action, Error> {
+        return self.schema(resource_type)?.validate_action(action);
+    }
+
+    /// Check that every filter of `query` is accepted by the collection of `resource_type`.
+    pub fn validate_query(&self, resource_type: &str, query: &Query) -> Result<(), Error> {
+        return self.schema(resource_type)?.validate_query(query);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use transport::{Method, MockTransport};
+    use test_util::rancher;
+
+    fn transport() -> Arc<MockTransport> {
+        let api_response = r#"{
+            "data": [
+                {
+                    "id": "volume",
+                    "type": "schema",
+                    "pluralName": "volumes",
+                    "resourceFields": {
+                        "name": {"type": "string", "create": true, "update": false, "required": true, "nullable": false},
+                        "driver": {"type": "string", "create": true, "update": false},
+                        "description": {"type": "string", "create": true, "update": true, "nullable": true},
+                        "accessMode": {"type": "enum", "create": true, "options": ["singleHostRW", "multiHostRW"]},
+                        "state": {"type": "string"}
+                    },
+                    "collectionFilters": {
+                        "name": {"modifiers": ["eq", "ne", "prefix"]},
+                        "state": {"modifiers": ["eq", "ne"]}
+                    },
+                    "resourceActions": {
+                        "activate": {"input": null, "output": "volume"},
+                        "restorefromsnapshot": {"input": "restoreFromSnapshotInput", "output": "volume"}
+                    },
+                    "collectionMethods": ["GET", "POST"],
+                    "resourceMethods": ["GET", "PUT", "DELETE"]
+                }
+            ]
+        }"#;
+        return Arc::new(MockTransport::new().on(Method::Get, "/schemas", 200, api_response));
+    }
+
+    #[test]
+    fn cache() {
+        let transport = transport();
+        let rancher = rancher(transport.clone());
+        assert_eq!(rancher.schemas().unwrap().types(), vec!["volume"]);
+        let schema = rancher.schemas().unwrap().get("volume").unwrap().clone();
+        assert_eq!(schema.plural_name, Some(String::from("volumes")));
+        assert_eq!(schema.resource_actions["restorefromsnapshot"].input, Some(String::from("restoreFromSnapshotInput")));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn validate() {
+        let rancher = rancher(transport());
+        let schemas = rancher.schemas().unwrap();
+
+        schemas.validate_create("volume", &json!({"name": "data", "accessMode": "multiHostRW"})).unwrap();
+        schemas.validate_create("volume", &json!({"driver": "local"})).unwrap_err();
+        schemas.validate_create("volume", &json!({"name": "data", "size": 10})).unwrap_err();
+        schemas.validate_create("volume", &json!({"name": "data", "accessMode": "everywhere"})).unwrap_err();
+        schemas.validate_create("volume", &json!({"name": "data", "state": "active"})).unwrap_err();
+        schemas.validate_update("volume", &json!({"description": null})).unwrap();
+        schemas.validate_update("volume", &json!({"name": "other"})).unwrap_err();
+        schemas.validate_create("snapshot", &json!({})).unwrap_err();
+
+        schemas.validate_action("volume", "activate").unwrap();
+        schemas.validate_action("volume", "evacuate").unwrap_err();
+
+        schemas.validate_query("volume", &Query::new().prefix("name", "data").sort("name").limit(10)).unwrap();
+        schemas.validate_query("volume", &Query::new().like("state", "act%")).unwrap_err();
+        schemas.validate_query("volume", &Query::new().eq("driver", "local")).unwrap_err();
+    }
+}
@@ -1,62 +1,118 @@
-use serde_json;
+use serde_json::{self, Map};
 use super::Rancher;
+use std::collections::HashMap;
+use error::Error;
+use model;
+use state::{ServiceState, HealthState, Transitioning};
+use resource::Resource;
 
 /// Struct that contains all data for a Rancher Service
 /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/service/).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Service {
-    /// Service unique ID.
+    /// Unique ID.
     pub id: String,
-    /// Service name.
-    pub name: String
+    /// Name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kind of resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// State.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ServiceState>,
+    /// Health of the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_state: Option<HealthState>,
+    /// ID of the stack of the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_id: Option<String>,
+    /// Requested number of containers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<u64>,
+    /// Current number of containers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_scale: Option<u64>,
+    /// Launch configuration of the primary containers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launch_config: Option<serde_json::Value>,
+    /// Launch configuration of the sidekicks.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub secondary_launch_configs: Vec<serde_json::Value>,
+    /// IDs of the containers of the service.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub instance_ids: Vec<String>,
+    /// Ports published by the service.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub public_endpoints: Vec<serde_json::Value>,
+    /// Fully qualified domain name of the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fqdn: Option<String>,
+    /// Label selector of the containers managed by the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector_container: Option<String>,
+    /// Label selector of the linked services.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector_link: Option<String>,
+    /// Virtual IP address of the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vip: Option<String>,
+    /// Whether the containers are started once created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_on_create: Option<bool>,
+    /// Whether the service is an infrastructure service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<bool>,
+    /// ID of the account owning the resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Creation date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Removal date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<String>,
+    /// Universally unique ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Whether an asynchronous operation is running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning: Option<Transitioning>,
+    /// Message of the running operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_message: Option<String>,
+    /// Progress of the running operation, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_progress: Option<u64>,
+    /// Links to the resource itself and its related resources.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub links: HashMap<String, String>,
+    /// Actions currently allowed on the resource, with their URL.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub actions: HashMap<String, String>,
+    /// Every other field returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
 }
 
 impl Service {
-    /// Get all services from an environment.
-    pub fn get_all(rancher: &mut Rancher, environment_id: &str) -> Result<Vec<Service>, &'static str> {
-        let mut data: Vec<Service> = Vec::new();
-        let path = format!("/projects/{}/services", environment_id);
-        let results = rancher.call_api(&path).unwrap();
-        let services = results["data"].as_array().unwrap();
-        for service in services {
-            data.push(Service{
-                id:     service["id"].as_str().unwrap().to_string(),
-                name:   service["name"].as_str().unwrap().to_string()
-            });
-        }
-        return Ok(data);
+    /// Set the number of containers of a service from an environment.
+    pub fn set_scale(rancher: &Rancher, environment_id: &str, service_id: &str, scale: u64) -> Result<Service, Error> {
+        return rancher.update::<Service>(environment_id, service_id, &json!({ "scale": scale }));
     }
-    /// Get a service from an environment and a pattern that should match with the service ID or
-    /// the service name.
-    pub fn get_one(rancher: &mut Rancher, environment_id: &str, pattern: &str) -> Result<Service, &'static str> {
-        let path = format!("/projects/{}/services", environment_id);
-        let results: serde_json::Value = rancher.call_api(&path).unwrap();
-        let services = results["data"].as_array().unwrap();
-        for service in services {
-            if pattern == service["id"].as_str().unwrap() || pattern == service["name"].as_str().unwrap() {
-                return Ok(Service{
-                    id:     service["id"].as_str().unwrap().to_string(),
-                    name:   service["name"].as_str().unwrap().to_string()
-                });
-            }
-        }
-        return Err("No service found.");
-    }
-}
-/// This trait allows you to call Service's functions with `rancher.get_service(.....)`.
-pub trait ServiceTrait {
-    /// Get all services from an environment.
-    fn get_services(&mut self, environment_id: &str) -> Result<Vec<Service>, &'static str>;
-    /// Get a service from an environment and a pattern that should match with the service ID of
-    /// the service name.
-    fn get_service(&mut self, environment_id: &str, pattern: &str) -> Result<Service, &'static str>;
 }
 
-impl ServiceTrait for Rancher {
-    fn get_services(&mut self, environment_id: &str) -> Result<Vec<Service>, &'static str> {
-        return Service::get_all(self, environment_id);
-    }
+impl Resource for Service {
+    type Parent = str;
+    type Id = str;
+
+    const TYPE: &'static str = "service";
 
-    fn get_service(&mut self, environment_id: &str, pattern: &str) -> Result<Service, &'static str> {
-        return Service::get_one(self, environment_id, pattern);
+    fn collection_path(environment_id: &str) -> String {
+        return format!("/projects/{}/services", environment_id);
     }
 }
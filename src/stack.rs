@@ -1,62 +1,97 @@
-use serde_json;
-use super::Rancher;
+use serde_json::{self, Map};
+use std::collections::HashMap;
+use model;
+use state::{ServiceState, HealthState, Transitioning};
+use resource::Resource;
 
 /// Struct that contains all data for a Rancher Stack
 /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/stack/)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Stack {
-    /// Stack unique ID.
+    /// Unique ID.
     pub id: String,
-    /// Stack name.
-    pub name: String
+    /// Name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kind of resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// State.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ServiceState>,
+    /// Health of the stack.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_state: Option<HealthState>,
+    /// Content of the `docker-compose.yml` file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker_compose: Option<String>,
+    /// Content of the `rancher-compose.yml` file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rancher_compose: Option<String>,
+    /// Environment variables used to interpolate the compose files.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub environment: HashMap<String, String>,
+    /// Catalog ID of the stack.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    /// Group of the stack.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// IDs of the services of the stack.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub service_ids: Vec<String>,
+    /// Whether the stack is an infrastructure stack.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<bool>,
+    /// Whether the services are started once created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_on_create: Option<bool>,
+    /// Outputs of the stack.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub outputs: HashMap<String, String>,
+    /// ID of the account owning the resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Creation date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Removal date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<String>,
+    /// Universally unique ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Whether an asynchronous operation is running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning: Option<Transitioning>,
+    /// Message of the running operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_message: Option<String>,
+    /// Progress of the running operation, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitioning_progress: Option<u64>,
+    /// Links to the resource itself and its related resources.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub links: HashMap<String, String>,
+    /// Actions currently allowed on the resource, with their URL.
+    #[serde(default, deserialize_with = "model::null_as_default")]
+    pub actions: HashMap<String, String>,
+    /// Every other field returned by the server.
+    #[serde(flatten)]
+    pub extra: Map<String, serde_json::Value>,
 }
 
-impl Stack {
-    /// Get all stacks from an environment.
-    pub fn get_all(rancher: &mut Rancher, environment_id: &str) -> Result<Vec<Stack>, &'static str> {
-        let mut data: Vec<Stack> = Vec::new();
-        let path = format!("/projects/{}/stacks", environment_id);
-        let results = rancher.call_api(&path).unwrap();
-        let stacks = results["data"].as_array().unwrap();
-        for stack in stacks {
-            data.push(Stack{
-                id: stack["id"].as_str().unwrap().to_string(),
-                name: stack["name"].as_str().unwrap().to_string()
-            });
-        }
-        return Ok(data);
-    }
-    /// Get a stack from an environment and a pattern that should match with the stack ID or the
-    /// stack name.
-    pub fn get_one(rancher: &mut Rancher, environment_id: &str, pattern: &str) -> Result<Stack, &'static str> {
-        let path = format!("/projects/{}/stacks", environment_id);
-        let results: serde_json::Value = rancher.call_api(&path).unwrap();
-        let stacks = results["data"].as_array().unwrap();
-        for stack in stacks {
-            if pattern == stack["id"].as_str().unwrap() || pattern == stack["name"].as_str().unwrap() {
-                return Ok(Stack{
-                    id: stack["id"].as_str().unwrap().to_string(),
-                    name: stack["name"].as_str().unwrap().to_string()
-                });
-            }
-        }
-        return Err("No stack found.");
-    }
-}
-/// This trait allows you to call Stack's functions with `rancher.get_stack(......)`.
-pub trait StackTrait {
-    /// Get all stacks from an environment.
-    fn get_stacks(&mut self, environment_id: &str) -> Result<Vec<Stack>, &'static str>;
-    /// Get a stack from an environment and a pattern that should match with the stack ID or the
-    /// stack name.
-    fn get_stack(&mut self, environment_id: &str, pattern: &str) -> Result<Stack, &'static str>;
-}
+impl Resource for Stack {
+    type Parent = str;
+    type Id = str;
 
-impl StackTrait for Rancher {
-    fn get_stacks(&mut self, environment_id: &str) -> Result<Vec<Stack>, &'static str> {
-        return Stack::get_all(self, environment_id);
-    }
+    const TYPE: &'static str = "stack";
 
-    fn get_stack(&mut self, environment_id: &str, pattern: &str) -> Result<Stack, &'static str> {
-        return Stack::get_one(self, environment_id, pattern);
+    fn collection_path(environment_id: &str) -> String {
+        return format!("/projects/{}/stacks", environment_id);
     }
 }
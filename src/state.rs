@@ -0,0 +1,349 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Define an enum mapped on the string states returned by the Rancher API, with an `Other`
+/// variant keeping any state unknown to this crate.
+macro_rules! state_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($(#[$vmeta:meta])* $variant:ident => $value:expr,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)*
+            /// Any state unknown to this crate.
+            Other(String),
+        }
+
+        impl $name {
+            /// Return the state as sent by the Rancher API.
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Other(ref value) => value,
+                }
+            }
+        }
+
+        impl<'a> From<&'a str> for $name {
+            fn from(value: &'a str) -> $name {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Other(value.to_string()),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                return f.write_str(self.as_str());
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                return serializer.serialize_str(self.as_str());
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                return Ok($name::from(value.as_str()));
+            }
+        }
+    };
+}
+
+state_enum! {
+    /// State of a container
+    /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/container/).
+    pub enum ContainerState {
+        /// Being created.
+        Creating => "creating",
+        /// Created but never started.
+        Created => "created",
+        /// Being started.
+        Starting => "starting",
+        /// Running.
+        Running => "running",
+        /// Being stopped.
+        Stopping => "stopping",
+        /// Stopped.
+        Stopped => "stopped",
+        /// Being restarted.
+        Restarting => "restarting",
+        /// Being updated.
+        Updating => "updating",
+        /// Being moved to another host.
+        Migrating => "migrating",
+        /// Being removed.
+        Removing => "removing",
+        /// Removed.
+        Removed => "removed",
+        /// Being purged.
+        Purging => "purging",
+        /// Purged.
+        Purged => "purged",
+        /// Failing.
+        Erroring => "erroring",
+        /// Failed.
+        Error => "error",
+    }
+}
+
+impl ContainerState {
+    /// Whether Rancher is moving the container from one state to another.
+    pub fn is_transitioning(&self) -> bool {
+        return matches!(*self,
+                        ContainerState::Creating | ContainerState::Starting | ContainerState::Stopping |
+                        ContainerState::Restarting | ContainerState::Updating | ContainerState::Migrating |
+                        ContainerState::Removing | ContainerState::Purging | ContainerState::Erroring);
+    }
+
+    /// Whether the container reached the end of its lifecycle.
+    pub fn is_terminal(&self) -> bool {
+        return matches!(*self, ContainerState::Removed | ContainerState::Purged);
+    }
+}
+
+state_enum! {
+    /// State of the agent of a host
+    /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/host/).
+    pub enum AgentState {
+        /// Connected.
+        Active => "active",
+        /// Connecting for the first time.
+        Activating => "activating",
+        /// Lost, Rancher is trying to reach it again.
+        Reconnecting => "reconnecting",
+        /// Reached again, finishing the reconnection.
+        FinishingReconnect => "finishing-reconnect",
+        /// Reconnected.
+        Reconnected => "reconnected",
+        /// Being disconnected.
+        Disconnecting => "disconnecting",
+        /// Disconnected.
+        Disconnected => "disconnected",
+    }
+}
+
+impl AgentState {
+    /// Whether Rancher is moving the agent from one state to another.
+    pub fn is_transitioning(&self) -> bool {
+        return matches!(*self,
+                        AgentState::Activating | AgentState::Reconnecting | AgentState::FinishingReconnect |
+                        AgentState::Disconnecting);
+    }
+
+    /// Whether the agent is disconnected for good.
+    pub fn is_terminal(&self) -> bool {
+        return *self == AgentState::Disconnected;
+    }
+}
+
+state_enum! {
+    /// State of a host
+    /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/host/).
+    pub enum HostState {
+        /// Being registered.
+        Registering => "registering",
+        /// Being provisioned by a machine driver.
+        Provisioning => "provisioning",
+        /// Being activated.
+        Activating => "activating",
+        /// Active.
+        Active => "active",
+        /// Being deactivated.
+        Deactivating => "deactivating",
+        /// Inactive, no container is scheduled on it.
+        Inactive => "inactive",
+        /// Being evacuated.
+        Evacuating => "evacuating",
+        /// Being updated.
+        Updating => "updating",
+        /// Being removed.
+        Removing => "removing",
+        /// Removed.
+        Removed => "removed",
+        /// Being purged.
+        Purging => "purging",
+        /// Purged.
+        Purged => "purged",
+        /// Failing.
+        Erroring => "erroring",
+        /// Failed.
+        Error => "error",
+    }
+}
+
+impl HostState {
+    /// Whether Rancher is moving the host from one state to another.
+    pub fn is_transitioning(&self) -> bool {
+        return matches!(*self,
+                        HostState::Registering | HostState::Provisioning | HostState::Activating |
+                        HostState::Deactivating | HostState::Evacuating | HostState::Updating |
+                        HostState::Removing | HostState::Purging | HostState::Erroring);
+    }
+
+    /// Whether the host reached the end of its lifecycle.
+    pub fn is_terminal(&self) -> bool {
+        return matches!(*self, HostState::Removed | HostState::Purged);
+    }
+}
+
+state_enum! {
+    /// Health of a container, a service, a stack or an environment.
+    pub enum HealthState {
+        /// Every healthcheck passes.
+        Healthy => "healthy",
+        /// Healthchecks fail.
+        Unhealthy => "unhealthy",
+        /// Waiting for the first healthcheck.
+        Initializing => "initializing",
+        /// Waiting for the first healthcheck after a restart.
+        Reinitializing => "reinitializing",
+        /// Some of the containers are unhealthy.
+        Degraded => "degraded",
+        /// Started once, for containers which are not meant to keep running.
+        StartedOnce => "started-once",
+        /// Being updated while healthy.
+        UpdatingHealthy => "updating-healthy",
+        /// Being updated while unhealthy.
+        UpdatingUnhealthy => "updating-unhealthy",
+    }
+}
+
+impl HealthState {
+    /// Whether the health is not settled yet.
+    pub fn is_transitioning(&self) -> bool {
+        return matches!(*self,
+                        HealthState::Initializing | HealthState::Reinitializing |
+                        HealthState::UpdatingHealthy | HealthState::UpdatingUnhealthy);
+    }
+
+    /// Whether the health won't change anymore.
+    pub fn is_terminal(&self) -> bool {
+        return *self == HealthState::StartedOnce;
+    }
+}
+
+state_enum! {
+    /// State of a service or a stack
+    /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/api-resources/service/).
+    pub enum ServiceState {
+        /// Being registered.
+        Registering => "registering",
+        /// Being activated.
+        Activating => "activating",
+        /// Active.
+        Active => "active",
+        /// Being updated while active.
+        UpdatingActive => "updating-active",
+        /// Being updated while inactive.
+        UpdatingInactive => "updating-inactive",
+        /// Being upgraded.
+        Upgrading => "upgrading",
+        /// Upgraded, waiting for the upgrade to be finished or rolled back.
+        Upgraded => "upgraded",
+        /// Finishing an upgrade.
+        FinishingUpgrade => "finishing-upgrade",
+        /// Rolling back an upgrade.
+        RollingBack => "rolling-back",
+        /// Canceling an upgrade.
+        CancelingUpgrade => "canceling-upgrade",
+        /// Upgrade canceled.
+        CanceledUpgrade => "canceled-upgrade",
+        /// Being restarted.
+        Restarting => "restarting",
+        /// Being deactivated.
+        Deactivating => "deactivating",
+        /// Inactive.
+        Inactive => "inactive",
+        /// Being removed.
+        Removing => "removing",
+        /// Removed.
+        Removed => "removed",
+        /// Failed.
+        Error => "error",
+    }
+}
+
+impl ServiceState {
+    /// Whether Rancher is moving the service from one state to another.
+    pub fn is_transitioning(&self) -> bool {
+        return matches!(*self,
+                        ServiceState::Registering | ServiceState::Activating | ServiceState::UpdatingActive |
+                        ServiceState::UpdatingInactive | ServiceState::Upgrading |
+                        ServiceState::FinishingUpgrade | ServiceState::RollingBack |
+                        ServiceState::CancelingUpgrade | ServiceState::Restarting |
+                        ServiceState::Deactivating | ServiceState::Removing);
+    }
+
+    /// Whether the service reached the end of its lifecycle.
+    pub fn is_terminal(&self) -> bool {
+        return *self == ServiceState::Removed;
+    }
+}
+
+state_enum! {
+    /// Allocation state of a container on its host.
+    pub enum AllocationState {
+        /// Being allocated.
+        Activating => "activating",
+        /// Allocated.
+        Active => "active",
+        /// Being released.
+        Deactivating => "deactivating",
+        /// Released.
+        Inactive => "inactive",
+    }
+}
+
+impl AllocationState {
+    /// Whether Rancher is allocating or releasing the resources.
+    pub fn is_transitioning(&self) -> bool {
+        return matches!(*self, AllocationState::Activating | AllocationState::Deactivating);
+    }
+
+    /// Whether the resources are released.
+    pub fn is_terminal(&self) -> bool {
+        return *self == AllocationState::Inactive;
+    }
+}
+
+state_enum! {
+    /// Whether Rancher is processing an asynchronous operation on a resource
+    /// (http://rancher.com/docs/rancher/v1.6/en/api/v2-beta/#resource-transitions).
+    pub enum Transitioning {
+        /// An operation is running.
+        Yes => "yes",
+        /// No operation is running.
+        No => "no",
+        /// The last operation failed, see `transitioningMessage`.
+        Error => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(ContainerState::from("running"), ContainerState::Running);
+        assert_eq!(AgentState::from("finishing-reconnect"), AgentState::FinishingReconnect);
+        assert_eq!(HealthState::from("sleepy"), HealthState::Other(String::from("sleepy")));
+        assert_eq!(HealthState::from("sleepy").as_str(), "sleepy");
+    }
+
+    #[test]
+    fn serde() {
+        let state: ServiceState = serde_json::from_str(r#""updating-active""#).unwrap();
+        assert_eq!(state, ServiceState::UpdatingActive);
+        assert!(state.is_transitioning());
+        assert!(!state.is_terminal());
+        assert_eq!(serde_json::to_string(&state).unwrap(), r#""updating-active""#);
+        let state: ContainerState = serde_json::from_str(r#""paused""#).unwrap();
+        assert_eq!(serde_json::to_string(&state).unwrap(), r#""paused""#);
+    }
+}
@@ -0,0 +1,30 @@
+//! Fixtures shared by the unit tests of the crate.
+use rancher::Rancher;
+use transport::Transport;
+#[cfg(feature = "async")]
+use async_rancher::AsyncRancher;
+#[cfg(feature = "async")]
+use transport::AsyncTransport;
+#[cfg(feature = "async")]
+use client_config::ClientConfig;
+
+/// URL of the Rancher server faked by the tests, so that `MockTransport` routes are the paths.
+pub(crate) const URL: &str = "http://rancher.test";
+
+/// Return a client of `URL` sending its requests through `transport`.
+pub(crate) fn rancher<T: Transport + 'static>(transport: T) -> Rancher {
+    return Rancher::with_transport(String::from(URL), String::from("access"), String::from("secret"), transport);
+}
+
+/// Return an asynchronous client of `URL` sending its requests through `transport`.
+#[cfg(feature = "async")]
+pub(crate) fn async_rancher<T: AsyncTransport + 'static>(transport: T) -> AsyncRancher {
+    return async_rancher_with_config(transport, ClientConfig::new());
+}
+
+/// Return an asynchronous client of `URL` sending its requests through `transport` with `config`.
+#[cfg(feature = "async")]
+pub(crate) fn async_rancher_with_config<T: AsyncTransport + 'static>(transport: T, config: ClientConfig) -> AsyncRancher {
+    return AsyncRancher::with_transport_and_config(String::from(URL), String::from("access"), String::from("secret"),
+                                                   transport, config);
+}
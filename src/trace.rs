@@ -0,0 +1,171 @@
+use std::time::Duration;
+use serde_json;
+use error::Error;
+use transport::{Method, Request, Response};
+
+/// Placeholder of redacted values.
+const REDACTED: &str = "<redacted>";
+
+/// Headers carrying credentials.
+const SECRET_HEADERS: [&str; 3] = ["authorization", "cookie", "proxy-authorization"];
+
+/// Parts of the names of JSON fields carrying credentials, compared in lower case.
+const SECRET_FIELDS: [&str; 5] = ["secret", "password", "token", "jwt", "privatekey"];
+
+/// Response to a request, passed to the hook installed with `ClientConfig::on_response`.
+///
+/// Credentials are redacted from the body.
+#[derive(Debug)]
+pub struct ResponseEvent<'a> {
+    /// Method of the request.
+    pub method: Method,
+    /// URL of the request.
+    pub url: &'a str,
+    /// Status code, unless the request failed.
+    pub status: Option<u32>,
+    /// Time from sending the request to receiving the whole response.
+    pub duration: Duration,
+    /// Body of the response, unless the request failed.
+    pub body: Option<&'a str>,
+    /// Error of the transport, if the request failed.
+    pub error: Option<&'a Error>,
+}
+
+/// Return a copy of `request` without credentials in its headers and body.
+pub(crate) fn redact_request(request: &Request) -> Request {
+    let headers = request.headers.iter()
+        .map(|(name, value)| {
+            if SECRET_HEADERS.contains(&name.to_lowercase().as_str()) {
+                (name.clone(), String::from(REDACTED))
+            } else {
+                (name.clone(), value.clone())
+            }
+        })
+        .collect();
+    return Request {
+        method: request.method,
+        url: request.url.clone(),
+        headers,
+        body: request.body.as_ref().map(|v| redact_body(v)),
+    };
+}
+
+/// Return `body` without the values of the fields carrying credentials, if it's JSON.
+pub(crate) fn redact_body(body: &str) -> String {
+    return match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        },
+        Err(_) => body.to_string(),
+    };
+}
+
+/// Replace the values of the fields carrying credentials in `value`, recursively.
+fn redact_value(value: &mut serde_json::Value) {
+    match *value {
+        serde_json::Value::Object(ref mut object) => {
+            // The login request sends `username:password` as its `code`.
+            let login = object.contains_key("authProvider");
+            for (key, value) in object.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_FIELDS.iter().any(|v| key.contains(v)) || (login && key == "code") {
+                    if !value.is_null() {
+                        *value = serde_json::Value::String(String::from(REDACTED));
+                    }
+                } else {
+                    redact_value(value);
+                }
+            }
+        },
+        serde_json::Value::Array(ref mut values) => {
+            for value in values {
+                redact_value(value);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Return whether anything is logged about requests.
+pub(crate) fn enabled() -> bool {
+    #[cfg(feature = "log")]
+    return log_enabled!(log::Level::Debug);
+    #[cfg(not(feature = "log"))]
+    return false;
+}
+
+/// Return whether bodies are logged, i.e. at trace level.
+pub(crate) fn bodies_enabled() -> bool {
+    #[cfg(feature = "log")]
+    return log_enabled!(log::Level::Trace);
+    #[cfg(not(feature = "log"))]
+    return false;
+}
+
+/// Log a request which is about to be sent: its method and URL, and its headers and body at
+/// trace level. `request` must already be redacted.
+#[cfg(feature = "log")]
+pub(crate) fn log_request(request: &Request) {
+    debug!("{} {}", request.method.as_str(), request.url);
+    if log_enabled!(log::Level::Trace) {
+        trace!("{} {} headers: {:?}, body: {}", request.method.as_str(), request.url, request.headers,
+               request.body.as_ref().map_or("", |v| v.as_str()));
+    }
+}
+
+/// Log a request which is about to be sent.
+#[cfg(not(feature = "log"))]
+pub(crate) fn log_request(_request: &Request) {}
+
+/// Log the outcome of a request: its status and duration, and the body at trace level. The body
+/// must already be redacted.
+#[cfg(feature = "log")]
+pub(crate) fn log_response(event: &ResponseEvent) {
+    match (event.status, event.error) {
+        (Some(status), _) => debug!("{} {} -> {} in {:?}", event.method.as_str(), event.url, status, event.duration),
+        (None, Some(error)) => debug!("{} {} failed in {:?}: {}", event.method.as_str(), event.url, event.duration, error),
+        (None, None) => debug!("{} {} failed in {:?}", event.method.as_str(), event.url, event.duration),
+    }
+    if let Some(body) = event.body {
+        trace!("{} {} response body: {}", event.method.as_str(), event.url, body);
+    }
+}
+
+/// Log the outcome of a request.
+#[cfg(not(feature = "log"))]
+pub(crate) fn log_response(_event: &ResponseEvent) {}
+
+/// Return the body of a response, redacted, for logs and hooks.
+pub(crate) fn response_body(response: &Response) -> String {
+    return redact_body(&String::from_utf8_lossy(&response.body));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact() {
+        let request = Request {
+            method: Method::Post,
+            url: String::from("http://rancher.test/v2-beta/token"),
+            headers: vec![
+                (String::from("Authorization"), String::from("Basic YWNjZXNzOnNlY3JldA==")),
+                (String::from("Content-Type"), String::from("application/json")),
+            ],
+            body: Some(String::from(r#"{"code": "admin:hunter2", "authProvider": "localauthconfig"}"#)),
+        };
+        let redacted = redact_request(&request);
+        assert_eq!(redacted.headers[0].1, REDACTED);
+        assert_eq!(redacted.headers[1].1, "application/json");
+        assert!(!redacted.body.unwrap().contains("hunter2"));
+
+        let body = redact_body(r#"{"data": [{"publicValue": "access", "secretValue": "s3cr3t", "jwt": "eyJ"}], "code": "NotFound"}"#);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap(), json!({
+            "data": [{"publicValue": "access", "secretValue": REDACTED, "jwt": REDACTED}],
+            "code": "NotFound"
+        }));
+        assert_eq!(redact_body("Bad Gateway"), "Bad Gateway");
+    }
+}
@@ -3,3 +3,318 @@ extern crate rancher;
 use rancher::*;
 
 
+extern crate serde_json;
+extern crate curl;
+
+use std::sync::Arc;
+use rancher::error::ApiError;
+use rancher::transport::{Method, MockTransport};
+
+#[test]
+fn api_error_from_value() {
+    let body: serde_json::Value = serde_json::from_str(r#"{
+        "id": "1a10",
+        "type": "error",
+        "baseType": "error",
+        "status": 404,
+        "code": "NotFound",
+        "message": "Not Found",
+        "detail": null
+    }"#).unwrap();
+    let error = ApiError::from_value(&body).unwrap();
+    assert_eq!(error.status, 404);
+    assert_eq!(error.code, "NotFound");
+    assert_eq!(error.detail, None);
+    let error = Error::Api(error);
+    assert_eq!(format!("{}", error), "Rancher API error: 404 NotFound: Not Found");
+
+    let body: serde_json::Value = serde_json::from_str(r#"{"type": "collection", "data": []}"#).unwrap();
+    assert!(ApiError::from_value(&body).is_none());
+}
+
+#[test]
+fn requests_are_authenticated() {
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Post, "/projects/1a5/containers/1i10?action=stop", 200, r#"{"id": "1i10", "type": "container"}"#));
+    let rancher = Rancher::with_transport(String::from("http://rancher.test"), String::from("access"),
+                                              String::from("secret"), transport.clone());
+    rancher.post_api_without_data("/projects/1a5/containers/1i10?action=stop").unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, Method::Post);
+    assert_eq!(requests[0].url, "http://rancher.test/projects/1a5/containers/1i10?action=stop");
+    assert!(requests[0].headers.contains(&(String::from("Authorization"), String::from("Basic YWNjZXNzOnNlY3JldA=="))));
+}
+
+#[test]
+fn models_keep_unknown_fields() {
+    let body = r#"{
+        "id": "1i10",
+        "type": "container",
+        "name": null,
+        "state": "running",
+        "labels": null,
+        "serviceIds": ["1s5"],
+        "nativeContainer": false,
+        "dataVolumes": ["/data:/data"]
+    }"#;
+    let container: Container = serde_json::from_str(body).unwrap();
+    assert_eq!(container.name, None);
+    assert!(container.labels.is_empty());
+    assert_eq!(container.service_ids, vec!["1s5"]);
+    assert_eq!(container.extra["type"], "container");
+    assert_eq!(container.extra["dataVolumes"][0], "/data:/data");
+
+    let value = serde_json::to_value(&container).unwrap();
+    assert_eq!(value["dataVolumes"][0], "/data:/data");
+    assert_eq!(value["serviceIds"][0], "1s5");
+    assert_eq!(value["nativeContainer"], false);
+    // Fields missing from the input, or null, stay out of the output.
+    assert!(value.get("hostId").is_none());
+    assert!(value.get("name").is_none());
+    let container: Container = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&container).unwrap(), value);
+}
+
+#[test]
+fn shared_across_threads() {
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Get, "/projects/1a5/hosts", 200, r#"{"data": [{"id": "1h10"}]}"#)
+        .on(Method::Get, "/projects/1a6/hosts", 200, r#"{"data": [{"id": "1h20"}, {"id": "1h21"}]}"#));
+    let rancher = Rancher::with_transport(String::from("http://rancher.test"), String::from("access"),
+                                          String::from("secret"), transport.clone());
+    let workers: Vec<_> = vec!["1a5", "1a6"].into_iter().map(|environment_id| {
+        let rancher = rancher.clone();
+        std::thread::spawn(move || rancher.list::<Host>(environment_id, &Query::new()).unwrap().len())
+    }).collect();
+    let counts: Vec<usize> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+
+    assert_eq!(counts, vec![1, 2]);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[test]
+fn retry_idempotent_requests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Get, "/projects/1a5/hosts", 503, "Service Unavailable")
+        .on(Method::Get, "/projects/1a5/hosts", 502, "Bad Gateway")
+        .on(Method::Get, "/projects/1a5/hosts", 200, r#"{"data": [{"id": "1h10"}]}"#)
+        .on(Method::Post, "/projects/1a5/hosts", 503, "Service Unavailable")
+        .on(Method::Get, "/projects/1a6/hosts", 501, "Not Implemented"));
+    let retries = Arc::new(AtomicUsize::new(0));
+    let counter = retries.clone();
+    let config = ClientConfig::new()
+        .retry(RetryPolicy::new().initial_backoff(Duration::from_millis(1)))
+        .on_retry(move |retry| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            assert!(retry.status.is_some());
+        });
+    let rancher = Rancher::with_transport_and_config(String::from("http://rancher.test"), String::from("access"),
+                                                     String::from("secret"), transport.clone(), config);
+
+    assert_eq!(rancher.list::<Host>("1a5", &Query::new()).unwrap().len(), 1);
+    assert_eq!(retries.load(Ordering::SeqCst), 2);
+    match rancher.post_api("/projects/1a5/hosts", "{}") {
+        Err(Error::Server(e)) => assert_eq!(e.status, 503),
+        other => panic!("Expected a 503, got {:?}", other)
+    }
+    assert_eq!(retries.load(Ordering::SeqCst), 2);
+    assert_eq!(transport.requests().len(), 4);
+    // 501 is permanent.
+    match rancher.list::<Host>("1a6", &Query::new()) {
+        Err(Error::Server(e)) => assert_eq!(e.status, 501),
+        other => panic!("Expected a 501, got {:?}", other)
+    }
+    assert_eq!(retries.load(Ordering::SeqCst), 2);
+    assert_eq!(transport.requests().len(), 5);
+}
+
+#[test]
+fn retry_connection_errors_only() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use rancher::transport::{Transport, Request, Response};
+
+    // Transport failing with the curl error `code`, one of the `CURLE_*` codes.
+    struct FailingTransport {
+        code: u32,
+        sent: AtomicUsize,
+    }
+
+    impl Transport for FailingTransport {
+        fn send(&self, _request: &Request) -> Result<Response, Error> {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Err(Error::Transport(curl::Error::new(self.code)))
+        }
+    }
+
+    let sent = |code| {
+        let transport = Arc::new(FailingTransport { code, sent: AtomicUsize::new(0) });
+        let config = ClientConfig::new().retry(RetryPolicy::new().max_retries(2).initial_backoff(Duration::from_millis(1)));
+        let rancher = Rancher::with_transport_and_config(String::from("http://rancher.test"), String::from("access"),
+                                                         String::from("secret"), transport.clone(), config);
+        assert!(rancher.call_api("/projects").is_err());
+        transport.sent.load(Ordering::SeqCst)
+    };
+    // CURLE_COULDNT_CONNECT
+    assert_eq!(sent(7), 3);
+    // CURLE_PEER_FAILED_VERIFICATION and CURLE_SSL_CACERT_BADFILE
+    assert_eq!(sent(60), 1);
+    assert_eq!(sent(77), 1);
+}
+
+#[test]
+fn default_environment() {
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Get, "/v2-beta/projects?name=monitoring&limit=1", 200, r#"{"data": [{"id": "1a5", "name": "monitoring"}]}"#)
+        .on(Method::Get, "/v2-beta/projects/1a5/containers", 200, r#"{"data": [{"id": "1i1"}]}"#));
+    let rancher = Rancher::builder()
+        .url("http://rancher.test/v2-beta")
+        .credentials("access", "secret")
+        .environment("monitoring")
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let environment = rancher.default_environment().unwrap();
+    assert_eq!(environment.id(), "1a5");
+    assert_eq!(environment.list::<Container>(&Query::new()).unwrap()[0].id, "1i1");
+    rancher.default_environment().unwrap();
+    // `/projects/monitoring` doesn't exist, the name is then looked up, only once.
+    assert_eq!(transport.requests().len(), 3);
+
+    // An ID is used as is, and an environment named like an ID is still found by name.
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Get, "/v2-beta/projects/1a5", 200, r#"{"id": "1a5", "name": "Default"}"#)
+        .on(Method::Get, "/v2-beta/projects?name=1a7&limit=1", 200, r#"{"data": [{"id": "1a9", "name": "1a7"}]}"#));
+    for &(environment, id) in &[("1a5", "1a5"), ("1a7", "1a9")] {
+        let rancher = Rancher::builder()
+            .url("http://rancher.test/v2-beta")
+            .credentials("access", "secret")
+            .environment(environment)
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        assert_eq!(rancher.environment_id().unwrap(), id);
+    }
+
+    let rancher = Rancher::builder()
+        .url("http://rancher.test/v2-beta")
+        .credentials("access", "secret")
+        .transport(transport)
+        .build()
+        .unwrap();
+    match rancher.default_environment() {
+        Err(Error::Config(_)) => {},
+        Err(e) => panic!("Expected Config, got {:?}", e),
+        Ok(_) => panic!("Expected Config, got an environment"),
+    }
+}
+
+#[test]
+fn login_and_refresh_session() {
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Post, "/v2-beta/token", 201, r#"{"type": "token", "jwt": "first"}"#)
+        .on(Method::Post, "/v2-beta/token", 201, r#"{"type": "token", "jwt": "second"}"#)
+        .on(Method::Get, "/v2-beta/projects", 200, r#"{"data": []}"#)
+        .on(Method::Get, "/v2-beta/projects", 401, r#"{"type": "error", "status": 401, "code": "Unauthorized"}"#)
+        .on(Method::Get, "/v2-beta/projects", 200, r#"{"data": []}"#));
+    let rancher = Rancher::builder()
+        .url("http://rancher.test/v2-beta")
+        .auth(Auth::login("admin", "password"))
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    rancher.call_api("/projects").unwrap();
+    rancher.call_api("/projects").unwrap();
+
+    let requests = transport.requests();
+    let cookie = |i: usize| requests[i].headers.iter().find(|h| h.0 == "Cookie").map(|h| h.1.clone());
+    assert_eq!(requests.len(), 5);
+    assert_eq!(requests[0].url, "http://rancher.test/v2-beta/token");
+    assert_eq!(requests[0].body, Some(String::from(r#"{"authProvider":"localauthconfig","code":"admin:password"}"#)));
+    assert_eq!(cookie(1), Some(String::from("token=first")));
+    assert_eq!(cookie(2), Some(String::from("token=first")));
+    assert_eq!(requests[3].url, "http://rancher.test/v2-beta/token");
+    assert_eq!(cookie(4), Some(String::from("token=second")));
+}
+
+#[test]
+fn request_and_response_hooks() {
+    use std::sync::Mutex;
+
+    let transport = Arc::new(MockTransport::new()
+        .on(Method::Post, "/projects/1a5/apikeys", 201, r#"{"id": "1c1", "publicValue": "access", "secretValue": "s3cr3t"}"#));
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let (seen_requests, seen_responses) = (requests.clone(), responses.clone());
+    let config = ClientConfig::new()
+        .on_request(move |request| seen_requests.lock().unwrap().push(request.clone()))
+        .on_response(move |response| seen_responses.lock().unwrap().push((response.status, response.body.map(|v| v.to_string()))));
+    let rancher = Rancher::with_transport_and_config(String::from("http://rancher.test"), String::from("access"),
+                                                     String::from("secret"), transport, config);
+    let result = rancher.post_api("/projects/1a5/apikeys", r#"{"name": "ci", "secretValue": "s3cr3t"}"#).unwrap();
+    // Only what is shown to hooks is redacted.
+    assert_eq!(result["secretValue"], "s3cr3t");
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].headers.contains(&(String::from("Authorization"), String::from("<redacted>"))));
+    assert!(!requests[0].body.as_ref().unwrap().contains("s3cr3t"));
+    let responses = responses.lock().unwrap();
+    assert_eq!(responses[0].0, Some(201));
+    let body = responses[0].1.as_ref().unwrap();
+    assert!(body.contains("\"publicValue\":\"access\"") && !body.contains("s3cr3t"));
+}
+
+#[test]
+fn status_codes_map_to_errors() {
+    let transport = MockTransport::new()
+        .on(Method::Get, "/unauthorized", 401, "<html><body>401 Authorization Required</body></html>")
+        .on(Method::Get, "/forbidden", 403, r#"{"type": "error", "status": 403, "code": "Forbidden", "detail": "read-only"}"#)
+        .on(Method::Get, "/conflict", 409, r#"{"type": "error", "status": 409, "code": "Conflict"}"#)
+        .on(Method::Post, "/invalid", 422, r#"{"type": "error", "status": 422, "code": "MissingRequired", "detail": "name"}"#)
+        .on(Method::Get, "/failing", 502, "Bad Gateway")
+        .on(Method::Get, "/bad", 400, r#"{"type": "error", "status": 400, "code": "InvalidAction"}"#)
+        .on(Method::Post, "/action", 200, r#"{"type": "error", "baseType": "error", "status": 422, "code": "InvalidState"}"#);
+    let rancher = Rancher::with_transport(String::from("http://rancher.test"), String::from("access"),
+                                          String::from("secret"), transport);
+
+    match rancher.call_api("/unauthorized") {
+        Err(Error::Unauthorized(e)) => assert_eq!(e.detail, Some(String::from("<html><body>401 Authorization Required</body></html>"))),
+        other => panic!("Expected Unauthorized, got {:?}", other)
+    }
+    match rancher.call_api("/forbidden") {
+        Err(Error::Forbidden(e)) => assert_eq!(e.detail, Some(String::from("read-only"))),
+        other => panic!("Expected Forbidden, got {:?}", other)
+    }
+    match rancher.call_api("/missing") {
+        Err(Error::NotFound { error: Some(e), .. }) => assert_eq!(e.code, "NotFound"),
+        other => panic!("Expected NotFound, got {:?}", other)
+    }
+    match rancher.call_api("/conflict") {
+        Err(Error::Conflict(_)) => {},
+        other => panic!("Expected Conflict, got {:?}", other)
+    }
+    match rancher.post_api("/invalid", "{}") {
+        Err(Error::UnprocessableEntity(e)) => assert_eq!((e.code.as_str(), e.detail), ("MissingRequired", Some(String::from("name")))),
+        other => panic!("Expected UnprocessableEntity, got {:?}", other)
+    }
+    match rancher.call_api("/failing") {
+        Err(Error::Server(e)) => assert_eq!(e.status, 502),
+        other => panic!("Expected Server, got {:?}", other)
+    }
+    match rancher.call_api("/bad") {
+        Err(Error::Api(e)) => assert_eq!(e.code, "InvalidAction"),
+        other => panic!("Expected Api, got {:?}", other)
+    }
+    match rancher.post_api_without_data("/action") {
+        Err(ref e @ Error::UnprocessableEntity(_)) => assert_eq!(e.status(), Some(422)),
+        other => panic!("Expected UnprocessableEntity, got {:?}", other)
+    }
+}